@@ -17,13 +17,16 @@ fn parse_current(value: &str) -> IResult<&str, bool> {
 }
 
 fn parse_name(value: &str) -> IResult<&str, String> {
+    context("name", map(take_till1(AsChar::is_space), String::from)).parse(value)
+}
+
+/// Parses the description git lists instead of a branch name when `HEAD` is detached, e.g.
+/// `(HEAD detached at abc1234)`
+fn parse_detached_head(value: &str) -> IResult<&str, String> {
     context(
-        "name",
+        "detached_head",
         map(
-            alt((
-                delimited(tag("("), take_while1(|c: char| c != ')'), tag(")")),
-                take_till1(AsChar::is_space),
-            )),
+            delimited(tag("("), take_while1(|c: char| c != ')'), tag(")")),
             String::from,
         ),
     )
@@ -58,32 +61,110 @@ fn parse_upstream_info(value: &str) -> IResult<&str, Option<String>> {
     .parse(value)
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SearchField {
+    Name,
+    Message,
+    Sha,
+    Upstream,
+    Author,
+}
+
+impl SearchField {
+    pub const ALL: [SearchField; 5] = [
+        SearchField::Name,
+        SearchField::Message,
+        SearchField::Sha,
+        SearchField::Upstream,
+        SearchField::Author,
+    ];
+
+    /// The prefix used in filter queries and in the `filter_fields` configuration
+    pub fn key(self) -> &'static str {
+        match self {
+            SearchField::Name => "name",
+            SearchField::Message => "msg",
+            SearchField::Sha => "sha",
+            SearchField::Upstream => "up",
+            SearchField::Author => "author",
+        }
+    }
+}
+
+impl FromStr for SearchField {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        SearchField::ALL
+            .into_iter()
+            .find(|field| field.key() == s)
+            .ok_or_else(|| anyhow!("Unknown filter field: {s}"))
+    }
+}
+
 pub trait Branch {
     fn name(&self) -> &str;
+
+    fn scope(&self) -> Option<&str> {
+        None
+    }
+
+    fn field(&self, field: SearchField) -> Option<&str> {
+        match field {
+            SearchField::Name => Some(self.name()),
+            _ => None,
+        }
+    }
 }
 
+/// A local branch listed by `git branch -vv`. When `HEAD` is detached, it's listed as a branch
+/// named after git's description of it, e.g. `HEAD detached at abc1234`, with `detached` set.
 #[derive(Default, Clone)]
 pub struct LocalBranch {
     pub name: String,
     pub current: bool,
+    pub detached: bool,
     pub commit_sha: String,
     pub upstream_info: Option<UpstreamInfo>,
     pub commit_message: String,
 }
 
+impl LocalBranch {
+    /// The revision to pass to git commands for this branch
+    pub fn rev(&self) -> &str {
+        if self.detached { "HEAD" } else { &self.name }
+    }
+}
+
 impl Branch for LocalBranch {
     fn name(&self) -> &str {
         &self.name
     }
+
+    fn field(&self, field: SearchField) -> Option<&str> {
+        match field {
+            SearchField::Name => Some(&self.name),
+            SearchField::Message => Some(&self.commit_message),
+            SearchField::Sha => Some(&self.commit_sha),
+            SearchField::Upstream => self
+                .upstream_info
+                .as_ref()
+                .map(|upstream_info| upstream_info.name.as_str()),
+            SearchField::Author => None,
+        }
+    }
 }
 
 impl FromStr for LocalBranch {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (current, name, commit_sha, upstream_info, commit_message) = (
+        let (current, (name, detached), commit_sha, upstream_info, commit_message) = (
             ws(parse_current),
-            ws(parse_name),
+            ws(alt((
+                map(parse_detached_head, |name| (name, true)),
+                map(parse_name, |name| (name, false)),
+            ))),
             ws(parse_commit_sha),
             ws(parse_upstream_info),
             parse_commit_message,
@@ -107,6 +188,7 @@ impl FromStr for LocalBranch {
         Ok(Self {
             name,
             current,
+            detached,
             commit_sha,
             upstream_info,
             commit_message,
@@ -116,8 +198,13 @@ impl FromStr for LocalBranch {
 
 #[derive(Clone)]
 pub enum RemoteBranchRef {
+    /// A symbolic ref pointing at another remote branch, like `origin/HEAD -> origin/main` which
+    /// marks the default branch of the remote
     Branch(String),
-    Commit { sha: String, message: String },
+    Commit {
+        sha: String,
+        message: String,
+    },
 }
 
 #[derive(Clone)]
@@ -141,10 +228,51 @@ impl RemoteBranch {
     }
 }
 
+impl RemoteBranch {
+    pub fn remote(&self) -> &str {
+        self.name
+            .split_once('/')
+            .map(|(remote, _)| remote)
+            .unwrap_or(&self.name)
+    }
+
+    /// The branch to track when this one is checked out. Symbolic refs are resolved to their
+    /// target, so that tracking `origin/HEAD` doesn't create a branch named `HEAD`.
+    pub fn tracked_name(&self) -> &str {
+        match &self.reference {
+            RemoteBranchRef::Branch(target) => target,
+            RemoteBranchRef::Commit { .. } => &self.name,
+        }
+    }
+
+    /// The name of the local branch created by tracking this one, i.e. the tracked name without
+    /// the remote
+    pub fn local_name(&self) -> &str {
+        let tracked_name = self.tracked_name();
+        tracked_name
+            .split_once('/')
+            .map(|(_, name)| name)
+            .unwrap_or(tracked_name)
+    }
+}
+
 impl Branch for RemoteBranch {
     fn name(&self) -> &str {
         &self.name
     }
+
+    fn scope(&self) -> Option<&str> {
+        Some(self.remote())
+    }
+
+    fn field(&self, field: SearchField) -> Option<&str> {
+        match (field, &self.reference) {
+            (SearchField::Name, _) => Some(&self.name),
+            (SearchField::Message, RemoteBranchRef::Commit { message, .. }) => Some(message),
+            (SearchField::Sha, RemoteBranchRef::Commit { sha, .. }) => Some(sha),
+            _ => None,
+        }
+    }
 }
 
 impl FromStr for RemoteBranch {
@@ -160,6 +288,33 @@ impl FromStr for RemoteBranch {
     }
 }
 
+pub fn parse_pruned_remote_branch(line: &str) -> Option<String> {
+    (
+        ws(tag::<_, _, nom::error::Error<&str>>("- [deleted]")),
+        take_until1("->"),
+        ws(tag("->")),
+        not_line_ending,
+    )
+        .parse(line)
+        .ok()
+        .map(|(_, (_, _, _, name))| String::from(name.trim()))
+}
+
+/// Parses the name and the former tip of a branch from `git branch -d` output, e.g.
+/// `Deleted branch topic (was 3333333).`
+pub fn parse_deleted_branch(line: &str) -> Option<(String, String)> {
+    (
+        tag::<_, _, nom::error::Error<&str>>("Deleted branch "),
+        take_until1(" (was "),
+        tag(" (was "),
+        hex_digit1,
+        tag(")"),
+    )
+        .parse(line.trim())
+        .ok()
+        .map(|(_, (_, name, _, sha, _))| (String::from(name), String::from(sha)))
+}
+
 #[derive(Debug, Clone)]
 pub struct UpstreamInfo {
     pub name: String,
@@ -0,0 +1,150 @@
+use std::ops::{Bound, RangeBounds};
+
+use zellij_tile::prelude::*;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Rect {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Span {
+    pub content: String,
+    pub colors: Vec<(usize, Vec<usize>)>,
+    pub selected: bool,
+}
+
+impl Span {
+    pub fn new(content: impl Into<String>) -> Self {
+        Self {
+            content: content.into(),
+            ..Self::default()
+        }
+    }
+
+    pub fn color_range(self, index_level: usize, range: impl RangeBounds<usize>) -> Self {
+        let start = match range.start_bound() {
+            Bound::Included(start) => *start,
+            Bound::Excluded(start) => start + 1,
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(end) => end + 1,
+            Bound::Excluded(end) => *end,
+            Bound::Unbounded => self.width(),
+        };
+        self.color_indices(index_level, (start..end).collect())
+    }
+
+    pub fn color_indices(mut self, index_level: usize, indices: Vec<usize>) -> Self {
+        self.colors.push((index_level, indices));
+        self
+    }
+
+    pub fn selected(mut self) -> Self {
+        self.selected = true;
+        self
+    }
+
+    pub fn width(&self) -> usize {
+        self.content.chars().count()
+    }
+
+    pub fn fit(mut self, width: usize) -> Self {
+        let content_width = self.width();
+        if content_width > width {
+            self.content = self.content.chars().take(width).collect();
+            for (_, indices) in &mut self.colors {
+                indices.retain(|index| *index < width);
+            }
+        } else {
+            self.content
+                .extend(std::iter::repeat_n(' ', width - content_width));
+        }
+        self
+    }
+
+    fn to_text(&self) -> Text {
+        let text = self
+            .colors
+            .iter()
+            .filter(|(_, indices)| !indices.is_empty())
+            .fold(Text::new(&self.content), |text, (index_level, indices)| {
+                text.color_indices(*index_level, indices.clone())
+            });
+        if self.selected { text.selected() } else { text }
+    }
+}
+
+pub trait Canvas {
+    fn print_text(&mut self, span: &Span, x: usize, y: usize);
+    fn print_ribbon(&mut self, span: &Span, x: usize, y: usize);
+}
+
+pub struct ZellijCanvas;
+
+impl Canvas for ZellijCanvas {
+    fn print_text(&mut self, span: &Span, x: usize, y: usize) {
+        print_text_with_coordinates(span.to_text(), x, y, None, None);
+    }
+
+    fn print_ribbon(&mut self, span: &Span, x: usize, y: usize) {
+        print_ribbon_with_coordinates(span.to_text(), x, y, None, None);
+    }
+}
+
+#[cfg(test)]
+pub struct GridCanvas {
+    cols: usize,
+    cells: Vec<Vec<char>>,
+}
+
+#[cfg(test)]
+impl GridCanvas {
+    pub fn new(rows: usize, cols: usize) -> Self {
+        Self {
+            cols,
+            cells: vec![vec![' '; cols]; rows],
+        }
+    }
+
+    pub fn snapshot(&self) -> String {
+        let mut lines: Vec<String> = self
+            .cells
+            .iter()
+            .map(|row| row.iter().collect::<String>().trim_end().to_string())
+            .collect();
+        while lines.last().is_some_and(String::is_empty) {
+            lines.pop();
+        }
+        lines.join("\n")
+    }
+
+    fn write(&mut self, content: &str, x: usize, y: usize) {
+        let cols = self.cols;
+        if let Some(row) = self.cells.get_mut(y) {
+            for (cell, c) in row.iter_mut().take(cols).skip(x).zip(content.chars()) {
+                *cell = c;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+impl Canvas for GridCanvas {
+    fn print_text(&mut self, span: &Span, x: usize, y: usize) {
+        self.write(&span.content, x, y);
+    }
+
+    fn print_ribbon(&mut self, span: &Span, x: usize, y: usize) {
+        let ribbon = if span.selected {
+            format!("[ {} ]", span.content)
+        } else {
+            format!("( {} )", span.content)
+        };
+        self.write(&ribbon, x, y);
+    }
+}
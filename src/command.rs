@@ -0,0 +1,400 @@
+use std::collections::BTreeMap;
+
+use anyhow::{anyhow, bail};
+
+use crate::reset::ResetMode;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    ListLocalBranches {
+        preserve_selection: bool,
+    },
+    ListRemoteBranches {
+        preserve_selection: bool,
+    },
+    ListRemotes,
+    Switch {
+        branch: String,
+    },
+    Create {
+        branch: String,
+    },
+    Delete {
+        branch: String,
+    },
+    RestoreBranch {
+        branch: String,
+        sha: String,
+    },
+    Fetch {
+        branch: String,
+    },
+    /// `background` is set for the periodic fetch, whose failures don't interrupt the user
+    FetchAll {
+        background: bool,
+    },
+    TrackRemote {
+        branch: String,
+    },
+    SetUpstream {
+        branch: String,
+        upstream: String,
+    },
+    UnsetUpstream {
+        branch: String,
+    },
+    CountLostCommits {
+        branch: String,
+        target: String,
+    },
+    Reset {
+        branch: String,
+        target: String,
+        mode: Option<ResetMode>,
+    },
+    ListCommits {
+        branch: String,
+    },
+    CherryPick {
+        commits: Vec<String>,
+    },
+    CherryPickContinue,
+    CherryPickSkip,
+    CherryPickAbort,
+    /// Checks whether a cherry-pick is in progress
+    CheckCherryPick,
+    ListLog {
+        skip: usize,
+        count: usize,
+    },
+    CheckoutDetached {
+        sha: String,
+    },
+    CreateBranchAt {
+        branch: String,
+        sha: String,
+    },
+    Tag {
+        tag: String,
+        sha: String,
+    },
+    AddRemote {
+        remote: String,
+    },
+    RenameRemote {
+        remote: String,
+    },
+    RemoveRemote {
+        remote: String,
+    },
+    SetRemoteUrl {
+        remote: String,
+    },
+    SetRemoteHead {
+        remote: String,
+    },
+}
+
+impl Command {
+    const KEY_COMMAND: &'static str = "command";
+    const KEY_GENERATION: &'static str = "generation";
+    const KEY_BRANCH: &'static str = "branch";
+    const KEY_UPSTREAM: &'static str = "upstream";
+    const KEY_SHA: &'static str = "sha";
+    const KEY_TARGET: &'static str = "target";
+    const KEY_MODE: &'static str = "mode";
+    const KEY_COMMITS: &'static str = "commits";
+    const KEY_SKIP: &'static str = "skip";
+    const KEY_COUNT: &'static str = "count";
+    const KEY_TAG: &'static str = "tag";
+    const KEY_REMOTE: &'static str = "remote";
+    const KEY_PRESERVE_SELECTION: &'static str = "preserve_selection";
+    const KEY_BACKGROUND: &'static str = "background";
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Command::ListLocalBranches { .. } => "list_local_branches",
+            Command::ListRemoteBranches { .. } => "list_remote_branches",
+            Command::ListRemotes => "list_remotes",
+            Command::Switch { .. } => "switch",
+            Command::Create { .. } => "create",
+            Command::Delete { .. } => "delete",
+            Command::RestoreBranch { .. } => "restore_branch",
+            Command::Fetch { .. } => "fetch",
+            Command::FetchAll { .. } => "fetch_all",
+            Command::TrackRemote { .. } => "track_remote",
+            Command::SetUpstream { .. } => "set_upstream",
+            Command::UnsetUpstream { .. } => "unset_upstream",
+            Command::CountLostCommits { .. } => "count_lost_commits",
+            Command::Reset { .. } => "reset",
+            Command::ListCommits { .. } => "list_commits",
+            Command::CherryPick { .. } => "cherry_pick",
+            Command::CherryPickContinue => "cherry_pick_continue",
+            Command::CherryPickSkip => "cherry_pick_skip",
+            Command::CherryPickAbort => "cherry_pick_abort",
+            Command::CheckCherryPick => "check_cherry_pick",
+            Command::ListLog { .. } => "list_log",
+            Command::CheckoutDetached { .. } => "checkout_detached",
+            Command::CreateBranchAt { .. } => "create_branch_at",
+            Command::Tag { .. } => "tag",
+            Command::AddRemote { .. } => "add_remote",
+            Command::RenameRemote { .. } => "rename_remote",
+            Command::RemoveRemote { .. } => "remove_remote",
+            Command::SetRemoteUrl { .. } => "set_remote_url",
+            Command::SetRemoteHead { .. } => "set_remote_head",
+        }
+    }
+
+    /// Human readable form of the command, e.g. `switch feature` or `fetch all`
+    pub fn description(&self) -> String {
+        let name = self.name().replace('_', " ");
+        match self.subject() {
+            Some(subject) => format!("{name} {subject}"),
+            None => name,
+        }
+    }
+
+    /// The branch or remote the command acts on
+    pub fn subject(&self) -> Option<&str> {
+        match self {
+            Command::ListLocalBranches { .. }
+            | Command::ListRemoteBranches { .. }
+            | Command::ListRemotes
+            | Command::FetchAll { .. }
+            | Command::CherryPick { .. }
+            | Command::CherryPickContinue
+            | Command::CherryPickSkip
+            | Command::CherryPickAbort
+            | Command::CheckCherryPick
+            | Command::ListLog { .. } => None,
+            Command::Switch { branch }
+            | Command::Create { branch }
+            | Command::Delete { branch }
+            | Command::RestoreBranch { branch, .. }
+            | Command::Fetch { branch }
+            | Command::TrackRemote { branch }
+            | Command::SetUpstream { branch, .. }
+            | Command::UnsetUpstream { branch }
+            | Command::CountLostCommits { branch, .. }
+            | Command::Reset { branch, .. }
+            | Command::ListCommits { branch }
+            | Command::CreateBranchAt { branch, .. } => Some(branch),
+            Command::CheckoutDetached { sha } => Some(sha),
+            Command::Tag { tag, .. } => Some(tag),
+            Command::AddRemote { remote }
+            | Command::RenameRemote { remote }
+            | Command::RemoveRemote { remote }
+            | Command::SetRemoteUrl { remote }
+            | Command::SetRemoteHead { remote } => Some(remote),
+        }
+    }
+
+    pub fn is_refresh(&self) -> bool {
+        matches!(
+            self,
+            Command::ListLocalBranches { .. }
+                | Command::ListRemoteBranches { .. }
+                | Command::ListRemotes
+                | Command::ListLog { .. }
+                | Command::CheckCherryPick
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommandContext {
+    pub command: Command,
+    pub generation: u64,
+}
+
+impl From<CommandContext> for BTreeMap<String, String> {
+    fn from(value: CommandContext) -> Self {
+        let mut context = BTreeMap::from([
+            (
+                String::from(Command::KEY_COMMAND),
+                String::from(value.command.name()),
+            ),
+            (
+                String::from(Command::KEY_GENERATION),
+                value.generation.to_string(),
+            ),
+        ]);
+        let mut insert = |key: &str, value: String| context.insert(String::from(key), value);
+        match value.command {
+            Command::ListLocalBranches { preserve_selection }
+            | Command::ListRemoteBranches { preserve_selection } => {
+                insert(
+                    Command::KEY_PRESERVE_SELECTION,
+                    preserve_selection.to_string(),
+                );
+            }
+            Command::Switch { branch }
+            | Command::Create { branch }
+            | Command::Delete { branch }
+            | Command::Fetch { branch }
+            | Command::TrackRemote { branch }
+            | Command::UnsetUpstream { branch }
+            | Command::ListCommits { branch } => {
+                insert(Command::KEY_BRANCH, branch);
+            }
+            Command::CherryPick { commits } => {
+                insert(Command::KEY_COMMITS, commits.join(" "));
+            }
+            Command::ListLog { skip, count } => {
+                insert(Command::KEY_SKIP, skip.to_string());
+                insert(Command::KEY_COUNT, count.to_string());
+            }
+            Command::CheckoutDetached { sha } => {
+                insert(Command::KEY_SHA, sha);
+            }
+            Command::CreateBranchAt { branch, sha } => {
+                insert(Command::KEY_BRANCH, branch);
+                insert(Command::KEY_SHA, sha);
+            }
+            Command::Tag { tag, sha } => {
+                insert(Command::KEY_TAG, tag);
+                insert(Command::KEY_SHA, sha);
+            }
+            Command::RestoreBranch { branch, sha } => {
+                insert(Command::KEY_BRANCH, branch);
+                insert(Command::KEY_SHA, sha);
+            }
+            Command::SetUpstream { branch, upstream } => {
+                insert(Command::KEY_BRANCH, branch);
+                insert(Command::KEY_UPSTREAM, upstream);
+            }
+            Command::CountLostCommits { branch, target } => {
+                insert(Command::KEY_BRANCH, branch);
+                insert(Command::KEY_TARGET, target);
+            }
+            Command::Reset {
+                branch,
+                target,
+                mode,
+            } => {
+                insert(Command::KEY_BRANCH, branch);
+                insert(Command::KEY_TARGET, target);
+                if let Some(mode) = mode {
+                    insert(Command::KEY_MODE, mode.to_string());
+                }
+            }
+            Command::AddRemote { remote }
+            | Command::RenameRemote { remote }
+            | Command::RemoveRemote { remote }
+            | Command::SetRemoteUrl { remote }
+            | Command::SetRemoteHead { remote } => {
+                insert(Command::KEY_REMOTE, remote);
+            }
+            Command::FetchAll { background } => {
+                insert(Command::KEY_BACKGROUND, background.to_string());
+            }
+            Command::ListRemotes
+            | Command::CherryPickContinue
+            | Command::CherryPickSkip
+            | Command::CherryPickAbort
+            | Command::CheckCherryPick => {}
+        }
+        context
+    }
+}
+
+impl TryFrom<&BTreeMap<String, String>> for CommandContext {
+    type Error = anyhow::Error;
+
+    fn try_from(context: &BTreeMap<String, String>) -> Result<Self, Self::Error> {
+        let get = |key: &str| {
+            context
+                .get(key)
+                .cloned()
+                .ok_or_else(|| anyhow!("Missing `{key}` in command context"))
+        };
+        let branch = || get(Command::KEY_BRANCH);
+        let remote = || get(Command::KEY_REMOTE);
+        let target = || get(Command::KEY_TARGET);
+        let sha = || get(Command::KEY_SHA);
+        let number = |key: &str| -> anyhow::Result<usize> {
+            get(key)?
+                .parse()
+                .map_err(|e| anyhow!("Invalid `{key}` in command context: {e}"))
+        };
+        let flag = |key: &str| context.get(key).is_some_and(|value| value == "true");
+        let preserve_selection = || flag(Command::KEY_PRESERVE_SELECTION);
+
+        let command = match get(Command::KEY_COMMAND)?.as_str() {
+            "list_local_branches" => Command::ListLocalBranches {
+                preserve_selection: preserve_selection(),
+            },
+            "list_remote_branches" => Command::ListRemoteBranches {
+                preserve_selection: preserve_selection(),
+            },
+            "list_remotes" => Command::ListRemotes,
+            "switch" => Command::Switch { branch: branch()? },
+            "create" => Command::Create { branch: branch()? },
+            "delete" => Command::Delete { branch: branch()? },
+            "restore_branch" => Command::RestoreBranch {
+                branch: branch()?,
+                sha: get(Command::KEY_SHA)?,
+            },
+            "fetch" => Command::Fetch { branch: branch()? },
+            "fetch_all" => Command::FetchAll {
+                background: flag(Command::KEY_BACKGROUND),
+            },
+            "track_remote" => Command::TrackRemote { branch: branch()? },
+            "set_upstream" => Command::SetUpstream {
+                branch: branch()?,
+                upstream: get(Command::KEY_UPSTREAM)?,
+            },
+            "unset_upstream" => Command::UnsetUpstream { branch: branch()? },
+            "count_lost_commits" => Command::CountLostCommits {
+                branch: branch()?,
+                target: target()?,
+            },
+            "list_commits" => Command::ListCommits { branch: branch()? },
+            "cherry_pick" => Command::CherryPick {
+                commits: get(Command::KEY_COMMITS)?
+                    .split_whitespace()
+                    .map(String::from)
+                    .collect(),
+            },
+            "cherry_pick_continue" => Command::CherryPickContinue,
+            "cherry_pick_skip" => Command::CherryPickSkip,
+            "cherry_pick_abort" => Command::CherryPickAbort,
+            "check_cherry_pick" => Command::CheckCherryPick,
+            "list_log" => Command::ListLog {
+                skip: number(Command::KEY_SKIP)?,
+                count: number(Command::KEY_COUNT)?,
+            },
+            "checkout_detached" => Command::CheckoutDetached { sha: sha()? },
+            "create_branch_at" => Command::CreateBranchAt {
+                branch: branch()?,
+                sha: sha()?,
+            },
+            "tag" => Command::Tag {
+                tag: get(Command::KEY_TAG)?,
+                sha: sha()?,
+            },
+            "reset" => Command::Reset {
+                branch: branch()?,
+                target: target()?,
+                mode: context
+                    .get(Command::KEY_MODE)
+                    .map(|mode| mode.parse())
+                    .transpose()?,
+            },
+            "add_remote" => Command::AddRemote { remote: remote()? },
+            "rename_remote" => Command::RenameRemote { remote: remote()? },
+            "remove_remote" => Command::RemoveRemote { remote: remote()? },
+            "set_remote_url" => Command::SetRemoteUrl { remote: remote()? },
+            "set_remote_head" => Command::SetRemoteHead { remote: remote()? },
+            command => bail!("Unknown command `{command}`"),
+        };
+        let generation = get(Command::KEY_GENERATION)?
+            .parse()
+            .map_err(|e| anyhow!("Invalid generation in command context: {e}"))?;
+
+        Ok(Self {
+            command,
+            generation,
+        })
+    }
+}
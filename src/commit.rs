@@ -0,0 +1,63 @@
+use std::str::FromStr;
+
+use nom::{
+    Parser,
+    bytes::complete::{tag, take_until, take_until1},
+    combinator::rest,
+    error::context,
+};
+
+use anyhow::anyhow;
+
+use crate::branch::{Branch, SearchField};
+
+/// A commit listed by `git log` with [`Commit::LOG_FORMAT`]. It is a `Branch` named by its sha, so
+/// that commits can be filtered and selected the same way as branches.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Commit {
+    pub sha: String,
+    pub author: String,
+    pub subject: String,
+}
+
+impl Commit {
+    pub const LOG_FORMAT: &'static str = "--format=%h%x09%an%x09%s";
+}
+
+impl Branch for Commit {
+    fn name(&self) -> &str {
+        &self.sha
+    }
+
+    fn field(&self, field: SearchField) -> Option<&str> {
+        match field {
+            SearchField::Name | SearchField::Sha => Some(&self.sha),
+            SearchField::Message => Some(&self.subject),
+            SearchField::Author => Some(&self.author),
+            SearchField::Upstream => None,
+        }
+    }
+}
+
+impl FromStr for Commit {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (sha, _, author, _, subject) = (
+            context("sha", take_until1::<_, _, nom::error::Error<&str>>("\t")),
+            tag("\t"),
+            context("author", take_until("\t")),
+            tag("\t"),
+            context("subject", rest),
+        )
+            .parse(s)
+            .map_err(|e| anyhow!("Failed to parse commit line: {}", e.to_owned()))?
+            .1;
+
+        Ok(Self {
+            sha: String::from(sha),
+            author: String::from(author),
+            subject: String::from(subject),
+        })
+    }
+}
@@ -0,0 +1,138 @@
+use nucleo_matcher::{
+    Matcher, Utf32Str,
+    pattern::{Atom, AtomKind, CaseMatching, Normalization, Pattern},
+};
+
+use crate::branch::{Branch, SearchField};
+
+/// A filter query. Words prefixed with a field key, e.g. `msg:fix` or `sha:abc12`, only match that
+/// field. The rest of the words match any of the default fields.
+pub struct Query {
+    unscoped: Pattern,
+    scoped: Vec<(SearchField, Atom)>,
+}
+
+pub struct Match {
+    pub name_matched: bool,
+    pub score: u32,
+    pub name_indices: Vec<usize>,
+}
+
+impl Query {
+    pub fn parse(input: &str) -> Self {
+        let mut query = Self {
+            unscoped: Pattern::default(),
+            scoped: Vec::new(),
+        };
+        for word in input.split_whitespace() {
+            let scoped_word = word
+                .split_once(':')
+                .and_then(|(key, word)| key.parse::<SearchField>().ok().map(|field| (field, word)));
+            match scoped_word {
+                Some((_, "")) => {}
+                Some((field, word)) => query.scoped.push((field, Self::atom(field, word))),
+                None => query.unscoped.atoms.push(Atom::parse(
+                    word,
+                    CaseMatching::Smart,
+                    Normalization::Smart,
+                )),
+            }
+        }
+        query
+    }
+
+    // Shas are looked up by their prefix, everything else is matched fuzzily
+    fn atom(field: SearchField, word: &str) -> Atom {
+        match field {
+            SearchField::Sha => Atom::new(
+                word,
+                CaseMatching::Ignore,
+                Normalization::Never,
+                AtomKind::Prefix,
+                false,
+            ),
+            _ => Atom::parse(word, CaseMatching::Smart, Normalization::Smart),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.unscoped.atoms.is_empty() && self.scoped.is_empty()
+    }
+
+    /// Matches the branch against the query. Words without a field prefix are matched against the
+    /// name first and only against the other `default_fields` when the name doesn't match.
+    pub fn match_branch(
+        &self,
+        branch: &impl Branch,
+        default_fields: &[SearchField],
+        matcher: &mut Matcher,
+        buf: &mut Vec<char>,
+    ) -> Option<Match> {
+        let mut branch_match = Match {
+            name_matched: false,
+            score: 0,
+            name_indices: Vec::new(),
+        };
+
+        if !self.unscoped.atoms.is_empty() {
+            let name_matched = default_fields.contains(&SearchField::Name)
+                && branch_match
+                    .match_name(|indices| {
+                        self.unscoped
+                            .indices(Utf32Str::new(branch.name(), buf), matcher, indices)
+                    })
+                    .is_some();
+            if !name_matched {
+                branch_match.score += default_fields
+                    .iter()
+                    .filter(|field| **field != SearchField::Name)
+                    .filter_map(|field| {
+                        let value = branch.field(*field)?;
+                        self.unscoped.score(Utf32Str::new(value, buf), matcher)
+                    })
+                    .max()?;
+            }
+        }
+        for (field, atom) in &self.scoped {
+            match field {
+                SearchField::Name => branch_match.match_name(|indices| {
+                    atom.indices(Utf32Str::new(branch.name(), buf), matcher, indices)
+                        .map(u32::from)
+                })?,
+                _ => {
+                    let value = branch.field(*field)?;
+                    branch_match.score +=
+                        u32::from(atom.score(Utf32Str::new(value, buf), matcher)?);
+                }
+            }
+        }
+
+        branch_match.name_indices.sort_unstable();
+        branch_match.name_indices.dedup();
+        Some(branch_match)
+    }
+}
+
+impl Match {
+    fn match_name(
+        &mut self,
+        match_indices: impl FnOnce(&mut Vec<u32>) -> Option<u32>,
+    ) -> Option<()> {
+        let mut indices = Vec::new();
+        self.score += match_indices(&mut indices)?;
+        self.name_matched = true;
+        self.name_indices
+            .extend(indices.into_iter().map(|index| index as usize));
+        Some(())
+    }
+}
+
+/// Whether everything matching `query` is known to match `previous` as well. That's the case when
+/// characters were only appended, unless the appended characters turn a word into a field prefix or
+/// the previous query contains syntax that longer words loosen, like negations.
+pub fn narrows(previous: &str, query: &str) -> bool {
+    query
+        .strip_prefix(previous)
+        .is_some_and(|appended| !appended.contains(':'))
+        && !previous.contains(['!', '$', '\\'])
+}
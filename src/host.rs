@@ -0,0 +1,90 @@
+use std::{collections::BTreeMap, path::PathBuf};
+
+#[cfg(test)]
+use std::cell::{Cell, RefCell};
+
+use zellij_tile::prelude::*;
+
+pub trait Host {
+    fn close(&self);
+    fn set_timeout(&self, seconds: f64);
+    fn open_command_pane(&self, command: CommandToRun, floating: bool);
+    fn change_host_folder(&self, path: PathBuf);
+    fn block_cli_pipe(&self, pipe_id: &str);
+    /// Sends `output` to a `zellij pipe` command and lets it finish
+    fn reply_to_cli_pipe(&self, pipe_id: &str, output: &str);
+}
+
+#[derive(Default)]
+pub struct ZellijHost;
+
+impl Host for ZellijHost {
+    fn close(&self) {
+        close_self();
+    }
+
+    fn set_timeout(&self, seconds: f64) {
+        set_timeout(seconds);
+    }
+
+    fn open_command_pane(&self, command: CommandToRun, floating: bool) {
+        if floating {
+            open_command_pane_floating(command, None, BTreeMap::new());
+        } else {
+            open_command_pane(command, BTreeMap::new());
+        }
+    }
+
+    fn change_host_folder(&self, path: PathBuf) {
+        change_host_folder(path);
+    }
+
+    fn block_cli_pipe(&self, pipe_id: &str) {
+        block_cli_pipe_input(pipe_id);
+    }
+
+    fn reply_to_cli_pipe(&self, pipe_id: &str, output: &str) {
+        cli_pipe_output(pipe_id, output);
+        unblock_cli_pipe_input(pipe_id);
+    }
+}
+
+#[cfg(test)]
+#[derive(Default)]
+pub struct RecordingHost {
+    pub closed: Cell<bool>,
+    pub timeouts: RefCell<Vec<f64>>,
+    pub command_panes: RefCell<Vec<(CommandToRun, bool)>>,
+    pub host_folders: RefCell<Vec<PathBuf>>,
+    pub blocked_pipes: RefCell<Vec<String>>,
+    pub pipe_replies: RefCell<Vec<(String, String)>>,
+}
+
+#[cfg(test)]
+impl Host for RecordingHost {
+    fn close(&self) {
+        self.closed.set(true);
+    }
+
+    fn set_timeout(&self, seconds: f64) {
+        self.timeouts.borrow_mut().push(seconds);
+    }
+
+    fn open_command_pane(&self, command: CommandToRun, floating: bool) {
+        self.command_panes.borrow_mut().push((command, floating));
+    }
+
+    fn change_host_folder(&self, path: PathBuf) {
+        self.host_folders.borrow_mut().push(path);
+    }
+
+    fn block_cli_pipe(&self, pipe_id: &str) {
+        self.blocked_pipes.borrow_mut().push(String::from(pipe_id));
+    }
+
+    fn reply_to_cli_pipe(&self, pipe_id: &str, output: &str) {
+        self.pipe_replies
+            .borrow_mut()
+            .push((String::from(pipe_id), String::from(output)));
+    }
+}
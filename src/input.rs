@@ -0,0 +1,218 @@
+use zellij_tile::prelude::*;
+
+use crate::canvas::Span;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InputChange {
+    Text,
+    Cursor,
+}
+
+/// Single line text input. The cursor is a byte offset into the text and always sits on a char
+/// boundary.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LineInput {
+    text: String,
+    cursor: usize,
+}
+
+impl From<&str> for LineInput {
+    fn from(text: &str) -> Self {
+        let mut input = Self::default();
+        input.insert_str(text);
+        input
+    }
+}
+
+impl LineInput {
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.text.clear();
+        self.cursor = 0;
+    }
+
+    pub fn insert(&mut self, c: char) {
+        self.text.insert(self.cursor, c);
+        self.cursor += c.len_utf8();
+    }
+
+    /// Inserts text at the cursor. Line breaks and other control characters are dropped, so that
+    /// pasted text stays on a single line. Returns whether anything was inserted.
+    pub fn insert_str(&mut self, text: &str) -> bool {
+        let mut inserted = false;
+        for c in text.chars().filter(|c| !c.is_control()) {
+            self.insert(c);
+            inserted = true;
+        }
+        inserted
+    }
+
+    pub fn delete_backward(&mut self) -> bool {
+        let start = self.previous_char_boundary();
+        self.delete_range(start, self.cursor)
+    }
+
+    pub fn delete_forward(&mut self) -> bool {
+        let end = self.next_char_boundary();
+        self.delete_range(self.cursor, end)
+    }
+
+    pub fn delete_word_backward(&mut self) -> bool {
+        let start = self.previous_word_start();
+        self.delete_range(start, self.cursor)
+    }
+
+    pub fn move_left(&mut self) -> bool {
+        self.move_to(self.previous_char_boundary())
+    }
+
+    pub fn move_right(&mut self) -> bool {
+        self.move_to(self.next_char_boundary())
+    }
+
+    pub fn move_word_left(&mut self) -> bool {
+        self.move_to(self.previous_word_start())
+    }
+
+    pub fn move_word_right(&mut self) -> bool {
+        self.move_to(self.next_word_end())
+    }
+
+    pub fn move_home(&mut self) -> bool {
+        self.move_to(0)
+    }
+
+    pub fn move_end(&mut self) -> bool {
+        self.move_to(self.text.len())
+    }
+
+    pub fn handle_key(&mut self, key: &KeyWithModifier) -> Option<InputChange> {
+        let text_changed = |changed: bool| changed.then_some(InputChange::Text);
+        let cursor_moved = |moved: bool| moved.then_some(InputChange::Cursor);
+        match key {
+            KeyWithModifier {
+                bare_key: BareKey::Char('w'),
+                key_modifiers,
+            } if key_modifiers.contains(&KeyModifier::Ctrl) => {
+                text_changed(self.delete_word_backward())
+            }
+            KeyWithModifier {
+                bare_key: BareKey::Char('u'),
+                key_modifiers,
+            } if key_modifiers.contains(&KeyModifier::Ctrl) => {
+                let changed = !self.text.is_empty();
+                self.clear();
+                text_changed(changed)
+            }
+            KeyWithModifier {
+                bare_key: BareKey::Char('b'),
+                key_modifiers,
+            } if key_modifiers.contains(&KeyModifier::Alt) => cursor_moved(self.move_word_left()),
+            KeyWithModifier {
+                bare_key: BareKey::Char('f'),
+                key_modifiers,
+            } if key_modifiers.contains(&KeyModifier::Alt) => cursor_moved(self.move_word_right()),
+            KeyWithModifier {
+                bare_key: BareKey::Char(c),
+                key_modifiers,
+            } if !key_modifiers.contains(&KeyModifier::Ctrl)
+                && !key_modifiers.contains(&KeyModifier::Alt)
+                && !c.is_control() =>
+            {
+                self.insert(*c);
+                Some(InputChange::Text)
+            }
+            KeyWithModifier {
+                bare_key: BareKey::Backspace,
+                ..
+            } => text_changed(self.delete_backward()),
+            KeyWithModifier {
+                bare_key: BareKey::Delete,
+                ..
+            } => text_changed(self.delete_forward()),
+            KeyWithModifier {
+                bare_key: BareKey::Left,
+                ..
+            } => cursor_moved(self.move_left()),
+            KeyWithModifier {
+                bare_key: BareKey::Right,
+                ..
+            } => cursor_moved(self.move_right()),
+            KeyWithModifier {
+                bare_key: BareKey::Home,
+                ..
+            } => cursor_moved(self.move_home()),
+            KeyWithModifier {
+                bare_key: BareKey::End,
+                ..
+            } => cursor_moved(self.move_end()),
+            _ => None,
+        }
+    }
+
+    /// Renders the prompt followed by the text with a `|` at the cursor. When the text doesn't fit,
+    /// its beginning is scrolled out of view so that the cursor stays visible.
+    pub fn to_span(&self, prompt: &str, width: usize) -> Span {
+        let before_cursor = &self.text[..self.cursor];
+        let after_cursor = &self.text[self.cursor..];
+        let available_width = width.saturating_sub(prompt.chars().count() + 1);
+        let hidden_chars = before_cursor
+            .chars()
+            .count()
+            .saturating_sub(available_width);
+        let visible_before_cursor: String = before_cursor.chars().skip(hidden_chars).collect();
+        Span::new(format!("{prompt}{visible_before_cursor}|{after_cursor}")).fit(width)
+    }
+
+    fn move_to(&mut self, cursor: usize) -> bool {
+        let moved = cursor != self.cursor;
+        self.cursor = cursor;
+        moved
+    }
+
+    fn delete_range(&mut self, start: usize, end: usize) -> bool {
+        self.text.replace_range(start..end, "");
+        self.cursor = start;
+        start != end
+    }
+
+    fn previous_char_boundary(&self) -> usize {
+        self.text[..self.cursor]
+            .char_indices()
+            .next_back()
+            .map_or(0, |(index, _)| index)
+    }
+
+    fn next_char_boundary(&self) -> usize {
+        self.text[self.cursor..]
+            .chars()
+            .next()
+            .map_or(self.cursor, |c| self.cursor + c.len_utf8())
+    }
+
+    fn previous_word_start(&self) -> usize {
+        let mut chars = self.text[..self.cursor].char_indices().rev().peekable();
+        while chars.next_if(|(_, c)| !c.is_alphanumeric()).is_some() {}
+        let mut start = 0;
+        while let Some((index, _)) = chars.next_if(|(_, c)| c.is_alphanumeric()) {
+            start = index;
+        }
+        start
+    }
+
+    fn next_word_end(&self) -> usize {
+        let mut chars = self.text[self.cursor..].char_indices().peekable();
+        while chars.next_if(|(_, c)| !c.is_alphanumeric()).is_some() {}
+        while chars.next_if(|(_, c)| c.is_alphanumeric()).is_some() {}
+        chars
+            .peek()
+            .map_or(self.text.len(), |(index, _)| self.cursor + index)
+    }
+}
@@ -1,42 +1,270 @@
 mod branch;
+mod canvas;
+mod command;
+mod commit;
+mod filter;
+mod host;
+mod input;
+mod message;
+mod pipe;
+mod remote;
+mod reset;
+mod runner;
 mod tab;
+mod table;
+#[cfg(test)]
+mod tests;
 
-use std::{collections::BTreeMap, io::BufRead, path::PathBuf};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io::BufRead,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
 
-use branch::{LocalBranch, RemoteBranch};
-use tab::Tab;
-use zellij_mason::Rect;
+use branch::{
+    LocalBranch, RemoteBranch, RemoteBranchRef, SearchField, UpstreamInfo, parse_deleted_branch,
+    parse_pruned_remote_branch,
+};
+use canvas::{Canvas, Rect, Span, ZellijCanvas};
+use command::{Command, CommandContext};
+use commit::Commit;
+use host::{Host, ZellijHost};
+use input::LineInput;
+use message::{Level, Message, Messages};
+use pipe::PipeCommand;
+use remote::Remote;
+use reset::{ResetMode, ResetPrompt, ResetState};
+use runner::{GitRunner, ZellijGitRunner};
+use tab::{CommitsTab, HelpEntry, RemotesTab, Tab};
 use zellij_tile::prelude::*;
 
-#[derive(Default, Clone, Copy)]
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
 enum BranchType {
     #[default]
     Local,
     Remote,
+    Remotes,
+    Commits,
+}
+
+struct UpstreamPicker {
+    branch_name: String,
+    tab: Tab<RemoteBranch>,
+}
+
+impl UpstreamPicker {
+    fn update_branches(&mut self, remote_branches: &[RemoteBranch]) {
+        self.tab.set_branches(
+            remote_branches
+                .iter()
+                .filter(|branch| matches!(branch.reference, RemoteBranchRef::Commit { .. }))
+                .cloned()
+                .collect(),
+        );
+    }
+}
+
+/// Commits of a branch that are not in `HEAD`, to be cherry-picked onto it
+struct CommitPicker {
+    branch_name: String,
+    tab: Tab<Commit>,
+    marked: BTreeSet<String>,
+}
+
+impl CommitPicker {
+    fn toggle_mark(&mut self) {
+        if let Some(sha) = self.tab.selected_branch_name()
+            && !self.marked.remove(&sha)
+        {
+            self.marked.insert(sha);
+        }
+        self.tab.select_down();
+    }
+
+    /// The marked commits, or the selected one when none are marked, oldest first
+    fn commits_to_pick(&self) -> Vec<String> {
+        if self.marked.is_empty() {
+            return self.tab.selected_branch_name().into_iter().collect();
+        }
+        self.tab
+            .view
+            .branches
+            .iter()
+            .rev()
+            .filter(|commit| self.marked.contains(&commit.sha))
+            .map(|commit| commit.sha.clone())
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum NameAction {
+    BranchFromHead,
+    BranchAt { sha: String },
+    TagAt { sha: String },
+    TrackAs { remote_branch: String },
+}
+
+/// A name being entered in the input line for an action that needs one
+struct NamePrompt {
+    action: NameAction,
+    input: LineInput,
+}
+
+impl NamePrompt {
+    const HELP: &'static [HelpEntry] = &[("<Enter>", "Create"), ("<Esc>", "Cancel")];
+
+    fn new(action: NameAction) -> Self {
+        Self {
+            action,
+            input: LineInput::default(),
+        }
+    }
+
+    fn prompt(&self) -> String {
+        match &self.action {
+            NameAction::BranchFromHead => String::from("Branch from HEAD: "),
+            NameAction::BranchAt { sha } => format!("Branch at {sha}: "),
+            NameAction::TagAt { sha } => format!("Tag at {sha}: "),
+            NameAction::TrackAs { remote_branch } => format!("Track {remote_branch} as: "),
+        }
+    }
+}
+
+/// A remote branch to be tracked by a new local branch whose name is already taken
+struct TrackCollision {
+    remote_branch: String,
+    local_branch: String,
+}
+
+impl TrackCollision {
+    const HELP: &'static [HelpEntry] = &[
+        ("s", "Switch to it"),
+        ("r", "Reset it"),
+        ("n", "New name"),
+        ("<Esc>", "Cancel"),
+    ];
+
+    fn to_span(&self, width: usize) -> Span {
+        Span::new(format!(
+            "{} already exists. Switch to it, reset it to {} or use a new name?",
+            self.local_branch, self.remote_branch
+        ))
+        .color_range(2, ..)
+        .fit(width)
+    }
+}
+
+/// A branch deleted in this session, kept so that the deletion can be undone
+#[derive(Debug, Clone, PartialEq)]
+struct DeletedBranch {
+    name: String,
+    sha: String,
 }
 
 #[derive(Default)]
-struct Git {
+struct Git<R = ZellijGitRunner, H = ZellijHost> {
+    runner: R,
+    host: H,
     cwd: Option<PathBuf>,
     open_log_in_floating: bool,
     log_args: Vec<String>,
     branch_type: BranchType,
     local_branches_tab: Tab<LocalBranch>,
     remote_branches_tab: Tab<RemoteBranch>,
-    error_message: Option<String>,
+    remotes_tab: RemotesTab,
+    commits_tab: CommitsTab,
+    upstream_picker: Option<UpstreamPicker>,
+    reset_prompt: Option<ResetPrompt>,
+    commit_picker: Option<CommitPicker>,
+    /// Whether the prompt of a cherry-pick stopped on a conflict is shown
+    cherry_pick_conflict: bool,
+    cherry_pick_in_progress: bool,
+    name_prompt: Option<NamePrompt>,
+    track_collision: Option<TrackCollision>,
+    deleted_branches: Vec<DeletedBranch>,
+    messages: Messages,
+    notice: Option<String>,
+    refresh_at: Option<Instant>,
+    fetch_interval: Option<Duration>,
+    next_fetch: Option<Instant>,
+    last_fetch: Option<Instant>,
+    /// When the minutes since the last fetch in the footer need to be updated
+    fetch_age_tick: Option<Instant>,
+    fetch_failed: bool,
+    displayed_fetch_minutes: Option<u64>,
+    fetching: bool,
+    generation: u64,
+    refresh_generations: BTreeMap<&'static str, u64>,
+    pending_commands: BTreeMap<u64, String>,
+    cli_pipes: BTreeMap<u64, String>,
+    spinner_frame: usize,
+    spinner_tick: Option<Instant>,
 }
 
-impl Git {
+impl<R: GitRunner, H: Host> Git<R, H> {
     const TEXT_LOCAL_TAB: &'static str = "Local";
     const TEXT_REMOTE_TAB: &'static str = "Remote";
+    const TEXT_REMOTES_TAB: &'static str = "Remotes";
+    const TEXT_COMMITS_TAB: &'static str = "Commits";
+    const TEXT_PANE_TOO_SMALL: &'static str = "Pane too small";
+    const TEXT_CHERRY_PICK_CONFLICT: &'static str =
+        "Cherry-pick stopped on a conflict. Resolve it, then continue.";
+    const REFRESH_DEBOUNCE_SECONDS: f64 = 0.5;
+    const UNDO_LIMIT: usize = 10;
+    const SPINNER_FRAMES: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+    const SPINNER_INTERVAL_SECONDS: f64 = 0.1;
+
+    fn run_git(&mut self, args: &[&str], command: Command) {
+        self.generation += 1;
+        let generation = self.generation;
+        if command.is_refresh() {
+            self.refresh_generations.insert(command.name(), generation);
+        } else {
+            self.pending_commands
+                .insert(generation, command.description());
+            if self.spinner_tick.is_none() {
+                self.schedule_spinner_tick();
+            }
+        }
+        self.runner.run(
+            self.cwd.as_deref(),
+            args,
+            CommandContext {
+                command,
+                generation,
+            },
+        );
+    }
+
+    fn schedule_spinner_tick(&mut self) {
+        self.spinner_tick =
+            Some(Instant::now() + Duration::from_secs_f64(Self::SPINNER_INTERVAL_SECONDS));
+        self.host.set_timeout(Self::SPINNER_INTERVAL_SECONDS);
+    }
+
+    fn is_superseded(&self, context: &CommandContext) -> bool {
+        context.command.is_refresh()
+            && self
+                .refresh_generations
+                .get(context.command.name())
+                .is_some_and(|generation| context.generation < *generation)
+    }
 
     fn successful_command_update(
         &mut self,
-        context: BTreeMap<String, String>,
+        context: CommandContext,
         stdout: Vec<u8>,
+        stderr: Vec<u8>,
     ) -> bool {
-        match context.get("command").map(String::as_str) {
-            Some("list_local_branches") => {
+        if let Some(status) = success_status(&context.command, &stdout) {
+            self.messages
+                .info(status, String::from_utf8_lossy(&stderr).trim_end());
+            self.host.set_timeout(Messages::STATUS_SECONDS);
+        }
+        match context.command {
+            Command::ListLocalBranches { preserve_selection } => {
                 let branches: anyhow::Result<Vec<LocalBranch>> = stdout
                     .lines()
                     .map_while(Result::ok)
@@ -45,24 +273,23 @@ impl Git {
 
                 match branches {
                     Ok(branches) => {
-                        self.local_branches_tab.view.table_state.select_index(
-                            branches
-                                .iter()
-                                .position(|branch| branch.current)
-                                .unwrap_or(0),
-                        );
-                        self.local_branches_tab.view.branches = branches;
-                        if !self.local_branches_tab.input.is_empty() {
-                            self.local_branches_tab.update_filtered_view();
+                        let current_branch_name = branches
+                            .iter()
+                            .find(|branch| branch.current)
+                            .map(|branch| branch.name.clone());
+                        let had_selection = self.local_branches_tab.selected_branch().is_some();
+                        self.local_branches_tab.set_branches(branches);
+                        if (!preserve_selection || !had_selection)
+                            && let Some(name) = current_branch_name
+                        {
+                            self.local_branches_tab.select_branch_by_name(&name);
                         }
-
-                        self.error_message = None;
                     }
-                    Err(err) => self.error_message = Some(err.to_string()),
+                    Err(err) => self.parse_error(&context.command, err),
                 }
                 true
             }
-            Some("list_remote_branches") => {
+            Command::ListRemoteBranches { preserve_selection } => {
                 let branches: anyhow::Result<Vec<RemoteBranch>> = stdout
                     .lines()
                     .map_while(Result::ok)
@@ -70,73 +297,556 @@ impl Git {
                     .collect();
 
                 match branches {
-                    Ok(branches) => {
-                        self.remote_branches_tab.view.branches = branches;
-                        if !self.remote_branches_tab.input.is_empty() {
-                            self.remote_branches_tab.update_filtered_view();
+                    Ok(mut branches) => {
+                        branches.sort_by(|a, b| a.remote().cmp(b.remote()));
+                        self.remote_branches_tab.set_branches(branches);
+                        if !preserve_selection {
+                            self.remote_branches_tab.select_first();
                         }
-
-                        self.error_message = None;
+                        if let Some(upstream_picker) = &mut self.upstream_picker {
+                            upstream_picker
+                                .update_branches(&self.remote_branches_tab.view.branches);
+                        }
+                    }
+                    Err(err) => self.parse_error(&context.command, err),
+                }
+                true
+            }
+            Command::Delete { .. } => {
+                if let Some((name, sha)) = stdout
+                    .lines()
+                    .map_while(Result::ok)
+                    .find_map(|line| parse_deleted_branch(&line))
+                {
+                    if self.deleted_branches.len() == Self::UNDO_LIMIT {
+                        self.deleted_branches.remove(0);
                     }
-                    Err(err) => self.error_message = Some(err.to_string()),
+                    self.deleted_branches.push(DeletedBranch { name, sha });
                 }
+                self.list_local_branches(true);
+                true
+            }
+            Command::Switch { .. }
+            | Command::RestoreBranch { .. }
+            | Command::Fetch { .. }
+            | Command::CheckoutDetached { .. }
+            | Command::CreateBranchAt { .. } => {
+                self.list_local_branches(true);
                 true
             }
-            Some("switch") | Some("create") | Some("delete") | Some("fetch") => {
-                self.list_local_branches();
+            Command::Create { .. } => {
+                self.list_local_branches(false);
                 true
             }
-            Some("track_remote") => {
+            Command::TrackRemote { .. } => {
                 self.branch_type = BranchType::Local;
-                self.list_local_branches();
+                self.list_local_branches(false);
+                true
+            }
+            Command::SetUpstream { branch, upstream } => {
+                self.local_branches_tab.update_branch(&branch, |branch| {
+                    branch.upstream_info = Some(UpstreamInfo {
+                        name: upstream.clone(),
+                        relationship: None,
+                    })
+                });
+                self.list_local_branches(true);
+                true
+            }
+            Command::CountLostCommits {
+                ref branch,
+                ref target,
+            } => {
+                match String::from_utf8_lossy(&stdout).trim().parse::<usize>() {
+                    Ok(lost_commits) => self.confirm_reset(branch, target, lost_commits),
+                    Err(err) => {
+                        self.reset_prompt = None;
+                        self.parse_error(&context.command, err.into());
+                    }
+                }
+                true
+            }
+            Command::Reset { .. } => {
+                self.list_local_branches(true);
+                true
+            }
+            Command::ListCommits { ref branch } => {
+                let commits: anyhow::Result<Vec<Commit>> = stdout
+                    .lines()
+                    .map_while(Result::ok)
+                    .map(|line| line.parse())
+                    .collect();
+                match commits {
+                    Ok(commits) => {
+                        if let Some(commit_picker) = self
+                            .commit_picker
+                            .as_mut()
+                            .filter(|commit_picker| &commit_picker.branch_name == branch)
+                        {
+                            commit_picker.tab.set_branches(commits);
+                            commit_picker.tab.select_first();
+                        }
+                    }
+                    Err(err) => self.parse_error(&context.command, err),
+                }
+                true
+            }
+            Command::CherryPick { .. }
+            | Command::CherryPickContinue
+            | Command::CherryPickSkip
+            | Command::CherryPickAbort => {
+                self.cherry_pick_conflict = false;
+                self.cherry_pick_in_progress = false;
+                self.list_local_branches(true);
+                true
+            }
+            Command::CheckCherryPick => {
+                // The prompt is opened once per cherry-pick, so that it stays hidden after `Esc`.
+                // It's opened again from the local tab with `Alt-c`.
+                if !self.cherry_pick_in_progress {
+                    self.cherry_pick_in_progress = true;
+                    self.cherry_pick_conflict = true;
+                    self.branch_type = BranchType::Local;
+                }
+                true
+            }
+            Command::ListLog { skip, count } => {
+                let commits: anyhow::Result<Vec<Commit>> = stdout
+                    .lines()
+                    .map_while(Result::ok)
+                    .map(|line| line.parse())
+                    .collect();
+                match commits {
+                    Ok(commits) => self.commits_tab.add_page(skip, count, commits),
+                    Err(err) => {
+                        self.commits_tab.loading = false;
+                        self.parse_error(&context.command, err);
+                    }
+                }
+                true
+            }
+            Command::Tag { .. } => true,
+            Command::UnsetUpstream { branch } => {
+                self.local_branches_tab
+                    .update_branch(&branch, |branch| branch.upstream_info = None);
+                self.list_local_branches(true);
+                true
+            }
+            Command::ListRemotes => {
+                match Remote::parse_list(&String::from_utf8_lossy(&stdout)) {
+                    Ok(remotes) => {
+                        if self
+                            .remotes_tab
+                            .table_state
+                            .selected_index()
+                            .is_none_or(|index| index >= remotes.len())
+                        {
+                            self.remotes_tab.table_state.select_index(0);
+                        }
+                        self.remotes_tab.remotes = remotes;
+                    }
+                    Err(err) => self.parse_error(&context.command, err),
+                }
+                true
+            }
+            Command::AddRemote { .. }
+            | Command::RenameRemote { .. }
+            | Command::RemoveRemote { .. }
+            | Command::SetRemoteUrl { .. } => {
+                self.remotes_tab.input.clear();
+                self.list_remotes();
+                self.list_remote_branches(true);
+                true
+            }
+            Command::SetRemoteHead { .. } => {
+                self.list_remote_branches(true);
+                true
+            }
+            Command::FetchAll { .. } => {
+                self.fetching = false;
+                self.fetch_failed = false;
+                self.last_fetch = Some(Instant::now());
+                if self.fetch_age_tick.is_none() {
+                    self.schedule_fetch_age_tick();
+                }
+                // A fetch that pruned nothing leaves a notice that hasn't been dismissed yet
+                if let Some(notice) = self.pruned_branches_notice(&stderr) {
+                    self.notice = Some(notice);
+                }
+                self.schedule_fetch();
+                self.list_local_branches(true);
+                self.list_remote_branches(true);
                 true
             }
-            _ => false,
         }
     }
 
-    fn list_local_branches(&self) {
-        let cmd = &["git", "branch", "-vv"];
-        let context =
-            BTreeMap::from([(String::from("command"), String::from("list_local_branches"))]);
-        match &self.cwd {
-            Some(cwd) => {
-                run_command_with_env_variables_and_cwd(cmd, BTreeMap::new(), cwd.clone(), context)
+    /// Handles a command that failed, or was interrupted when `exit_code` is `None`
+    fn failed_command_update(
+        &mut self,
+        context: CommandContext,
+        exit_code: Option<i32>,
+        stderr: Vec<u8>,
+    ) {
+        let title = match exit_code {
+            Some(exit_code) => format!(
+                "{} failed with exit code {exit_code}",
+                context.command.description()
+            ),
+            None => format!("{} was interrupted", context.command.description()),
+        };
+        let body = String::from_utf8_lossy(&stderr).trim_end().to_string();
+        match &context.command {
+            Command::FetchAll { background } => {
+                self.fetching = false;
+                self.schedule_fetch();
+                // The periodic fetch fails over and over while offline, so its errors are only
+                // kept in the history and pointed at from the footer
+                if *background {
+                    self.fetch_failed = true;
+                    self.messages.push(Message {
+                        level: Level::Error,
+                        title,
+                        body,
+                    });
+                    return;
+                }
+            }
+            Command::RestoreBranch { branch, sha } => {
+                self.deleted_branches.push(DeletedBranch {
+                    name: branch.clone(),
+                    sha: sha.clone(),
+                });
+            }
+            Command::CountLostCommits { .. } => self.reset_prompt = None,
+            Command::ListCommits { .. } => self.commit_picker = None,
+            Command::ListLog { .. } => self.commits_tab.loading = false,
+            Command::CherryPick { .. } | Command::CherryPickContinue | Command::CherryPickSkip => {
+                self.cherry_pick_in_progress = false;
+                self.check_cherry_pick();
+            }
+            // `rev-parse` fails when no cherry-pick is in progress
+            Command::CheckCherryPick => {
+                self.cherry_pick_conflict = false;
+                self.cherry_pick_in_progress = false;
+                return;
             }
-            None => run_command(cmd, context),
+            _ => {}
+        }
+        self.messages.error(title, body);
+    }
+
+    fn parse_error(&mut self, command: &Command, err: anyhow::Error) {
+        self.messages.error(
+            format!("Failed to parse the output of {}", command.name()),
+            err.to_string(),
+        );
+    }
+
+    fn pruned_branches_notice(&self, stderr: &[u8]) -> Option<String> {
+        let pruned_branches: Vec<String> = stderr
+            .lines()
+            .map_while(Result::ok)
+            .filter_map(|line| parse_pruned_remote_branch(&line))
+            .collect();
+        if pruned_branches.is_empty() {
+            return None;
+        }
+
+        let tracking_branches: Vec<&str> = self
+            .local_branches_tab
+            .view
+            .branches
+            .iter()
+            .filter(|branch| {
+                branch
+                    .upstream_info
+                    .as_ref()
+                    .is_some_and(|upstream_info| pruned_branches.contains(&upstream_info.name))
+            })
+            .map(|branch| branch.name.as_str())
+            .collect();
+        let mut notice = format!("Pruned {}", pruned_branches.join(", "));
+        if !tracking_branches.is_empty() {
+            notice.push_str(&format!(
+                ". Local branches tracking them: {}",
+                tracking_branches.join(", ")
+            ));
+        }
+        Some(notice)
+    }
+
+    fn list_local_branches(&mut self, preserve_selection: bool) {
+        self.run_git(
+            &["branch", "-vv"],
+            Command::ListLocalBranches { preserve_selection },
+        );
+    }
+
+    fn list_remote_branches(&mut self, preserve_selection: bool) {
+        self.run_git(
+            &["branch", "-r", "-v"],
+            Command::ListRemoteBranches { preserve_selection },
+        );
+    }
+
+    fn list_remotes(&mut self) {
+        self.run_git(&["remote", "-v"], Command::ListRemotes);
+    }
+
+    fn list_log(&mut self, skip: usize, count: usize) {
+        self.commits_tab.loading = true;
+        self.run_git(
+            &[
+                "log",
+                "--all",
+                Commit::LOG_FORMAT,
+                &format!("--skip={skip}"),
+                "-n",
+                &count.to_string(),
+            ],
+            Command::ListLog { skip, count },
+        );
+    }
+
+    /// Reloads as many commits as are loaded, at least a page
+    fn refresh_commits(&mut self) {
+        let count = self
+            .commits_tab
+            .commits
+            .view
+            .branches
+            .len()
+            .max(CommitsTab::PAGE_SIZE);
+        self.list_log(0, count);
+    }
+
+    fn load_more_commits(&mut self) {
+        if self.commits_tab.needs_more() {
+            self.list_log(
+                self.commits_tab.commits.view.branches.len(),
+                CommitsTab::PAGE_SIZE,
+            );
+        }
+    }
+
+    fn handle_file_system_event(&mut self, paths: &[(PathBuf, Option<FileMetadata>)]) -> bool {
+        if self.refresh_at.is_none() && paths.iter().any(|(path, _)| is_repository_state_path(path))
+        {
+            self.refresh_at =
+                Some(Instant::now() + Duration::from_secs_f64(Self::REFRESH_DEBOUNCE_SECONDS));
+            self.host.set_timeout(Self::REFRESH_DEBOUNCE_SECONDS);
         }
+        false
     }
 
-    fn list_remote_branches(&self) {
-        let cmd = &["git", "branch", "-r", "-v"];
-        let context = BTreeMap::from([(
-            String::from("command"),
-            String::from("list_remote_branches"),
-        )]);
-        match &self.cwd {
-            Some(cwd) => {
-                run_command_with_env_variables_and_cwd(cmd, BTreeMap::new(), cwd.clone(), context)
+    fn handle_timer(&mut self) -> bool {
+        let now = Instant::now();
+        let mut should_render = self.messages.expire_status(now);
+        if self.spinner_tick.is_some_and(|tick| now >= tick) {
+            self.spinner_tick = None;
+            if !self.pending_commands.is_empty() {
+                self.spinner_frame = (self.spinner_frame + 1) % Self::SPINNER_FRAMES.len();
+                self.schedule_spinner_tick();
+                should_render = true;
             }
-            None => run_command(cmd, context),
+        }
+        // Timers of the spinner and the periodic fetch fire here too, so the refresh waits for its
+        // own deadline
+        if self.refresh_at.is_some_and(|refresh_at| now >= refresh_at) {
+            self.refresh_at = None;
+            self.refresh();
+        }
+        if self.next_fetch.is_some_and(|next_fetch| now >= next_fetch) {
+            self.next_fetch = None;
+            self.fetch_all(true);
+        }
+        if self.fetch_age_tick.is_some_and(|tick| now >= tick) {
+            self.fetch_age_tick = None;
+            self.schedule_fetch_age_tick();
+        }
+        should_render || self.minutes_since_fetch() != self.displayed_fetch_minutes
+    }
+
+    /// Reloads the lists that have been displayed
+    fn refresh(&mut self) {
+        self.reload(true);
+    }
+
+    fn reload(&mut self, preserve_selection: bool) {
+        if self.local_branches_tab.inited {
+            self.list_local_branches(preserve_selection);
+            self.check_cherry_pick();
+        }
+        if self.remote_branches_tab.inited {
+            self.list_remote_branches(preserve_selection);
+        }
+        if self.remotes_tab.inited {
+            self.list_remotes();
+        }
+        if self.commits_tab.commits.inited {
+            self.refresh_commits();
+        }
+    }
+
+    /// Switches to the repository at `cwd`, forgetting what was known about the previous one and
+    /// reloading the lists that have been displayed
+    fn change_repository(&mut self, cwd: PathBuf) {
+        self.cwd = Some(cwd.clone());
+        self.host.change_host_folder(cwd);
+        self.deleted_branches.clear();
+        self.cherry_pick_conflict = false;
+        self.cherry_pick_in_progress = false;
+        self.notice = None;
+        self.last_fetch = None;
+        self.fetch_age_tick = None;
+        self.fetch_failed = false;
+        self.remotes_tab.remotes.clear();
+        self.commits_tab.commits.set_branches(Vec::new());
+        self.commits_tab.exhausted = false;
+        self.reload(false);
+    }
+
+    fn minutes_since_fetch(&self) -> Option<u64> {
+        self.last_fetch
+            .map(|last_fetch| last_fetch.elapsed().as_secs() / 60)
+    }
+
+    /// Schedules a timer for when the minutes since the last fetch go up next, so that the footer
+    /// is updated without other events
+    fn schedule_fetch_age_tick(&mut self) {
+        let Some(last_fetch) = self.last_fetch else {
+            return;
+        };
+        let seconds = 60.0 - last_fetch.elapsed().as_secs_f64() % 60.0;
+        self.fetch_age_tick = Some(Instant::now() + Duration::from_secs_f64(seconds));
+        self.host.set_timeout(seconds);
+    }
+
+    fn schedule_fetch(&mut self) {
+        if let Some(fetch_interval) = self.fetch_interval {
+            self.next_fetch = Some(Instant::now() + fetch_interval);
+            self.host.set_timeout(fetch_interval.as_secs_f64());
         }
     }
 
+    /// Whether a prompt or picker is waiting for the user's answer
+    fn has_open_prompt(&self) -> bool {
+        self.upstream_picker.is_some()
+            || self.reset_prompt.is_some()
+            || self.commit_picker.is_some()
+            || self.cherry_pick_conflict
+            || self.track_collision.is_some()
+            || self.name_prompt.is_some()
+            || self.remotes_tab.removal.is_some()
+    }
+
     fn handle_key_input(&mut self, key: KeyWithModifier) -> bool {
-        if self.error_message.is_some() {
-            self.error_message = None;
+        if self.handle_messages_key_input(&key) {
             return true;
         }
+        if self.upstream_picker.is_some() {
+            return self.handle_upstream_picker_key_input(key);
+        }
+        if self.reset_prompt.is_some() {
+            return self.handle_reset_prompt_key_input(key);
+        }
+        if self.commit_picker.is_some() {
+            return self.handle_commit_picker_key_input(key);
+        }
+        if self.cherry_pick_conflict {
+            return self.handle_cherry_pick_conflict_key_input(key);
+        }
+        if self.track_collision.is_some() {
+            return self.handle_track_collision_key_input(key);
+        }
+        if self.name_prompt.is_some() {
+            return self.handle_name_prompt_key_input(key);
+        }
+        if self.remotes_tab.removal.is_some() {
+            return self.handle_remote_removal_key_input(key);
+        }
         if let KeyWithModifier {
             bare_key: BareKey::Esc,
             ..
         } = key
         {
-            close_self();
+            // A notice is dismissed before the plugin is closed
+            if self.notice.take().is_none() {
+                self.host.close();
+            }
             return true;
         }
         match self.branch_type {
             BranchType::Local => self.handle_local_tab_key_input(key),
             BranchType::Remote => self.handle_remote_tab_key_input(key),
+            BranchType::Remotes => self.handle_remotes_tab_key_input(key),
+            BranchType::Commits => self.handle_commits_tab_key_input(key),
+        }
+    }
+
+    /// Inserts pasted text into the input that has the focus. Line breaks are dropped, so that a
+    /// pasted line doesn't run the action of `Enter`.
+    fn handle_paste(&mut self, text: &str) -> bool {
+        if let Some(name_prompt) = &mut self.name_prompt {
+            return name_prompt.input.insert_str(text);
+        }
+        if let Some(upstream_picker) = &mut self.upstream_picker {
+            return upstream_picker.tab.paste(text);
+        }
+        if let Some(commit_picker) = &mut self.commit_picker {
+            return commit_picker.tab.paste(text);
+        }
+        if self.reset_prompt.is_some()
+            || self.cherry_pick_conflict
+            || self.track_collision.is_some()
+            || self.remotes_tab.removal.is_some()
+        {
+            return false;
+        }
+        match self.branch_type {
+            BranchType::Local => self.local_branches_tab.paste(text),
+            BranchType::Remote => self.remote_branches_tab.paste(text),
+            BranchType::Remotes => self.remotes_tab.input.insert_str(text),
+            BranchType::Commits => {
+                let pasted = self.commits_tab.commits.paste(text);
+                self.load_more_commits();
+                pasted
+            }
+        }
+    }
+
+    fn handle_messages_key_input(&mut self, key: &KeyWithModifier) -> bool {
+        match key {
+            KeyWithModifier {
+                bare_key: BareKey::Char('m'),
+                key_modifiers,
+            } if key_modifiers.contains(&KeyModifier::Alt) => {
+                self.messages.toggle_history();
+                true
+            }
+            KeyWithModifier {
+                bare_key: BareKey::Esc,
+                ..
+            } if self.messages.is_open() => {
+                self.messages.dismiss();
+                true
+            }
+            KeyWithModifier {
+                bare_key: BareKey::PageUp,
+                ..
+            } if self.messages.is_open() => {
+                self.messages.page_up();
+                true
+            }
+            KeyWithModifier {
+                bare_key: BareKey::PageDown,
+                ..
+            } if self.messages.is_open() => {
+                self.messages.page_down();
+                true
+            }
+            _ => false,
         }
     }
 
@@ -166,9 +876,9 @@ impl Git {
             KeyWithModifier {
                 bare_key: BareKey::Enter,
                 ..
-            } => match self.local_branches_tab.current_view().selected_branch() {
+            } => match self.local_branches_tab.selected_branch().cloned() {
                 Some(branch) => {
-                    self.switch_to_branch(branch);
+                    self.switch_to_branch(&branch);
                     true
                 }
                 None => false,
@@ -177,7 +887,8 @@ impl Git {
                 bare_key: BareKey::Char('c'),
                 key_modifiers,
             } if key_modifiers.contains(&KeyModifier::Ctrl) => {
-                self.local_branches_tab.create_branch(self.cwd.as_ref());
+                let branch_name = String::from(self.local_branches_tab.input.as_str());
+                self.create_branch(&branch_name, None);
                 true
             }
 
@@ -185,17 +896,20 @@ impl Git {
                 bare_key: BareKey::Char('r'),
                 key_modifiers,
             } if key_modifiers.contains(&KeyModifier::Ctrl) => {
-                self.list_local_branches();
+                self.list_local_branches(true);
                 true
             }
             KeyWithModifier {
                 bare_key: BareKey::Char('d'),
                 key_modifiers,
             } if key_modifiers.contains(&KeyModifier::Ctrl) => {
-                if let Some(selected_branch) =
-                    self.local_branches_tab.current_view().selected_branch()
+                if let Some(selected_branch_name) = self
+                    .local_branches_tab
+                    .selected_branch()
+                    .filter(|branch| !branch.detached)
+                    .map(|branch| branch.name.clone())
                 {
-                    self.delete_branch(&selected_branch.name, false);
+                    self.delete_branch(&selected_branch_name, false);
                     true
                 } else {
                     false
@@ -205,23 +919,90 @@ impl Git {
                 bare_key: BareKey::Char('x'),
                 key_modifiers,
             } if key_modifiers.contains(&KeyModifier::Ctrl) => {
-                if let Some(selected_branch) =
-                    self.local_branches_tab.current_view().selected_branch()
+                if let Some(selected_branch_name) = self
+                    .local_branches_tab
+                    .selected_branch()
+                    .filter(|branch| !branch.detached)
+                    .map(|branch| branch.name.clone())
                 {
-                    self.delete_branch(&selected_branch.name, true);
+                    self.delete_branch(&selected_branch_name, true);
                     true
                 } else {
                     false
                 }
             }
             KeyWithModifier {
-                bare_key: BareKey::Char('l'),
+                bare_key: BareKey::Char('z'),
+                key_modifiers,
+            } if key_modifiers.contains(&KeyModifier::Ctrl) => self.undo_delete(),
+            KeyWithModifier {
+                bare_key: BareKey::Char('c'),
+                key_modifiers,
+            } if key_modifiers.contains(&KeyModifier::Alt) && self.cherry_pick_in_progress => {
+                self.cherry_pick_conflict = true;
+                true
+            }
+            KeyWithModifier {
+                bare_key: BareKey::Char('c'),
+                key_modifiers,
+            } if key_modifiers.contains(&KeyModifier::Alt) => {
+                if let Some(selected_branch) = self.local_branches_tab.selected_branch() {
+                    self.open_commit_picker(String::from(selected_branch.rev()));
+                    true
+                } else {
+                    false
+                }
+            }
+            KeyWithModifier {
+                bare_key: BareKey::Char('n'),
+                key_modifiers,
+            } if key_modifiers.contains(&KeyModifier::Alt)
+                && self
+                    .local_branches_tab
+                    .selected_branch()
+                    .is_some_and(|branch| branch.detached) =>
+            {
+                self.name_prompt = Some(NamePrompt::new(NameAction::BranchFromHead));
+                true
+            }
+            KeyWithModifier {
+                bare_key: BareKey::Char('e'),
                 key_modifiers,
             } if key_modifiers.contains(&KeyModifier::Ctrl) => {
-                if let Some(selected_branch) =
-                    self.local_branches_tab.current_view().selected_branch()
+                match self
+                    .local_branches_tab
+                    .selected_branch()
+                    .map(|branch| String::from(branch.rev()))
                 {
-                    self.open_log_pane(&selected_branch.name);
+                    Some(selected_branch_rev) => {
+                        if let Err(err) = self.reset_current_branch_to(&selected_branch_rev) {
+                            self.messages.error(err.to_string(), "");
+                        }
+                        true
+                    }
+                    None => false,
+                }
+            }
+            KeyWithModifier {
+                bare_key: BareKey::Char('e'),
+                key_modifiers,
+            } if key_modifiers.contains(&KeyModifier::Alt) => {
+                match self.local_branches_tab.selected_branch().cloned() {
+                    Some(selected_branch) => {
+                        if let Err(err) = self.reset_to_upstream(&selected_branch) {
+                            self.messages.error(err.to_string(), "");
+                        }
+                        true
+                    }
+                    None => false,
+                }
+            }
+            KeyWithModifier {
+                bare_key: BareKey::Char('l'),
+                key_modifiers,
+            } if key_modifiers.contains(&KeyModifier::Ctrl) => {
+                if let Some(selected_branch) = self.local_branches_tab.selected_branch() {
+                    self.open_log_pane(selected_branch.rev());
                     true
                 } else {
                     false
@@ -235,35 +1016,48 @@ impl Git {
                 true
             }
             KeyWithModifier {
-                bare_key: BareKey::Char('f'),
+                bare_key: BareKey::Char('t'),
                 key_modifiers,
             } if key_modifiers.contains(&KeyModifier::Ctrl) => {
-                if let Some(selected_branch) =
-                    self.local_branches_tab.current_view().selected_branch()
+                if let Some(selected_branch_name) = self
+                    .local_branches_tab
+                    .selected_branch()
+                    .filter(|branch| !branch.detached)
+                    .map(|branch| branch.name.clone())
                 {
-                    if let Err(err) = self.fetch(selected_branch) {
-                        self.error_message = Some(err.to_string());
-                    }
+                    self.open_upstream_picker(selected_branch_name);
                     true
                 } else {
                     false
                 }
             }
             KeyWithModifier {
-                bare_key: BareKey::Char(c),
-                ..
-            } => {
-                self.local_branches_tab.push_to_input(c);
-                true
+                bare_key: BareKey::Char('t'),
+                key_modifiers,
+            } if key_modifiers.contains(&KeyModifier::Alt) => {
+                if let Some(selected_branch) = self.local_branches_tab.selected_branch().cloned() {
+                    if let Err(err) = self.unset_upstream(&selected_branch) {
+                        self.messages.error(err.to_string(), "");
+                    }
+                    true
+                } else {
+                    false
+                }
             }
             KeyWithModifier {
-                bare_key: BareKey::Backspace,
-                ..
-            } => {
-                self.local_branches_tab.pop_from_input();
-                true
-            }
-            _ => false,
+                bare_key: BareKey::Char('f'),
+                key_modifiers,
+            } if key_modifiers.contains(&KeyModifier::Ctrl) => {
+                if let Some(selected_branch) = self.local_branches_tab.selected_branch().cloned() {
+                    if let Err(err) = self.fetch(&selected_branch) {
+                        self.messages.error(err.to_string(), "");
+                    }
+                    true
+                } else {
+                    false
+                }
+            }
+            _ => self.local_branches_tab.handle_input_key(&key),
         }
     }
 
@@ -273,7 +1067,7 @@ impl Git {
                 bare_key: BareKey::Tab,
                 ..
             } => {
-                self.branch_type = BranchType::Local;
+                self.branch_type = BranchType::Remotes;
                 true
             }
             KeyWithModifier {
@@ -293,9 +1087,9 @@ impl Git {
             KeyWithModifier {
                 bare_key: BareKey::Enter,
                 ..
-            } => match self.remote_branches_tab.current_view().selected_branch() {
+            } => match self.remote_branches_tab.selected_branch().cloned() {
                 Some(branch) => {
-                    self.track_remote_branch(branch);
+                    self.track_remote_branch(&branch);
                     true
                 }
                 None => false,
@@ -304,68 +1098,664 @@ impl Git {
                 bare_key: BareKey::Char('r'),
                 key_modifiers,
             } if key_modifiers.contains(&KeyModifier::Ctrl) => {
-                self.list_remote_branches();
+                self.list_remote_branches(true);
+                true
+            }
+            KeyWithModifier {
+                bare_key: BareKey::Char('f'),
+                key_modifiers,
+            } if key_modifiers.contains(&KeyModifier::Ctrl) => {
+                self.fetch_all(false);
+                true
+            }
+            KeyWithModifier {
+                bare_key: BareKey::Char('o'),
+                key_modifiers,
+            } if key_modifiers.contains(&KeyModifier::Ctrl) => {
+                self.remote_branches_tab.cycle_scope();
                 true
             }
+            KeyWithModifier {
+                bare_key: BareKey::Char('d'),
+                key_modifiers,
+            } if key_modifiers.contains(&KeyModifier::Alt) => {
+                if let Some(selected_branch) = self.remote_branches_tab.selected_branch() {
+                    let remote = String::from(selected_branch.remote());
+                    self.run_remote_command(
+                        &["set-head", &remote, "--auto"],
+                        Command::SetRemoteHead {
+                            remote: remote.clone(),
+                        },
+                    );
+                    true
+                } else {
+                    false
+                }
+            }
             KeyWithModifier {
                 bare_key: BareKey::Char('l'),
                 key_modifiers,
             } if key_modifiers.contains(&KeyModifier::Ctrl) => {
-                if let Some(selected_branch) =
-                    self.remote_branches_tab.current_view().selected_branch()
-                {
+                if let Some(selected_branch) = self.remote_branches_tab.selected_branch() {
                     self.open_log_pane(&selected_branch.name);
                     true
                 } else {
                     false
                 }
             }
+            _ => self.remote_branches_tab.handle_input_key(&key),
+        }
+    }
+
+    fn handle_upstream_picker_key_input(&mut self, key: KeyWithModifier) -> bool {
+        let Some(upstream_picker) = &mut self.upstream_picker else {
+            return false;
+        };
+        match key {
+            KeyWithModifier {
+                bare_key: BareKey::Esc,
+                ..
+            } => {
+                self.upstream_picker = None;
+                true
+            }
+            KeyWithModifier {
+                bare_key: BareKey::Down,
+                ..
+            } => {
+                upstream_picker.tab.select_down();
+                true
+            }
+            KeyWithModifier {
+                bare_key: BareKey::Up,
+                ..
+            } => {
+                upstream_picker.tab.select_up();
+                true
+            }
+            KeyWithModifier {
+                bare_key: BareKey::Enter,
+                ..
+            } => match upstream_picker.tab.selected_branch_name() {
+                Some(upstream) => {
+                    let branch_name = upstream_picker.branch_name.clone();
+                    self.upstream_picker = None;
+                    self.set_upstream(&branch_name, &upstream);
+                    true
+                }
+                None => false,
+            },
+            _ => upstream_picker.tab.handle_input_key(&key),
+        }
+    }
+
+    fn handle_reset_prompt_key_input(&mut self, key: KeyWithModifier) -> bool {
+        let Some(reset_prompt) = &mut self.reset_prompt else {
+            return false;
+        };
+        match (key, reset_prompt.state) {
+            (
+                KeyWithModifier {
+                    bare_key: BareKey::Esc,
+                    ..
+                },
+                _,
+            ) => {
+                self.reset_prompt = None;
+                true
+            }
+            (
+                KeyWithModifier {
+                    bare_key: BareKey::Char(c),
+                    ..
+                },
+                ResetState::ChoosingMode,
+            ) => match ResetPrompt::mode_for_key(c) {
+                Some(ResetMode::Hard) => {
+                    reset_prompt.mode = Some(ResetMode::Hard);
+                    reset_prompt.state = ResetState::CountingCommits;
+                    let (branch, target) =
+                        (reset_prompt.branch.clone(), reset_prompt.target.clone());
+                    self.count_lost_commits(&branch, &target);
+                    true
+                }
+                Some(mode) => {
+                    reset_prompt.mode = Some(mode);
+                    self.reset();
+                    true
+                }
+                None => false,
+            },
+            (
+                KeyWithModifier {
+                    bare_key: BareKey::Char('y'),
+                    ..
+                },
+                ResetState::Confirming { .. },
+            ) => {
+                self.reset();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn handle_commit_picker_key_input(&mut self, key: KeyWithModifier) -> bool {
+        let Some(commit_picker) = &mut self.commit_picker else {
+            return false;
+        };
+        match key {
+            KeyWithModifier {
+                bare_key: BareKey::Esc,
+                ..
+            } => {
+                self.commit_picker = None;
+                true
+            }
+            KeyWithModifier {
+                bare_key: BareKey::Down,
+                ..
+            } => {
+                commit_picker.tab.select_down();
+                true
+            }
+            KeyWithModifier {
+                bare_key: BareKey::Up,
+                ..
+            } => {
+                commit_picker.tab.select_up();
+                true
+            }
+            KeyWithModifier {
+                bare_key: BareKey::Tab,
+                ..
+            } => {
+                commit_picker.toggle_mark();
+                true
+            }
+            KeyWithModifier {
+                bare_key: BareKey::Enter,
+                ..
+            } => {
+                let commits = commit_picker.commits_to_pick();
+                if commits.is_empty() {
+                    return false;
+                }
+                self.commit_picker = None;
+                self.cherry_pick(commits);
+                true
+            }
+            _ => commit_picker.tab.handle_input_key(&key),
+        }
+    }
+
+    fn check_cherry_pick(&mut self) {
+        self.run_git(
+            &["rev-parse", "-q", "--verify", "CHERRY_PICK_HEAD"],
+            Command::CheckCherryPick,
+        );
+    }
+
+    fn handle_cherry_pick_conflict_key_input(&mut self, key: KeyWithModifier) -> bool {
+        match key {
+            KeyWithModifier {
+                bare_key: BareKey::Esc,
+                ..
+            } => {
+                self.cherry_pick_conflict = false;
+                true
+            }
+            KeyWithModifier {
+                bare_key: BareKey::Char('c'),
+                ..
+            } => {
+                self.run_git(
+                    &["-c", "core.editor=true", "cherry-pick", "--continue"],
+                    Command::CherryPickContinue,
+                );
+                true
+            }
+            KeyWithModifier {
+                bare_key: BareKey::Char('s'),
+                ..
+            } => {
+                self.run_git(&["cherry-pick", "--skip"], Command::CherryPickSkip);
+                true
+            }
+            KeyWithModifier {
+                bare_key: BareKey::Char('a'),
+                ..
+            } => {
+                self.run_git(&["cherry-pick", "--abort"], Command::CherryPickAbort);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn handle_commits_tab_key_input(&mut self, key: KeyWithModifier) -> bool {
+        match key {
+            KeyWithModifier {
+                bare_key: BareKey::Tab,
+                ..
+            } => {
+                self.branch_type = BranchType::Local;
+                true
+            }
+            KeyWithModifier {
+                bare_key: BareKey::Down,
+                ..
+            } => {
+                self.commits_tab.commits.select_down();
+                self.load_more_commits();
+                true
+            }
+            KeyWithModifier {
+                bare_key: BareKey::Up,
+                ..
+            } => {
+                self.commits_tab.commits.select_up();
+                true
+            }
+            KeyWithModifier {
+                bare_key: BareKey::Enter,
+                ..
+            } => match self.commits_tab.commits.selected_branch_name() {
+                Some(sha) => {
+                    self.run_git(
+                        &["switch", "--detach", &sha],
+                        Command::CheckoutDetached { sha: sha.clone() },
+                    );
+                    true
+                }
+                None => false,
+            },
+            KeyWithModifier {
+                bare_key: BareKey::Char('r'),
+                key_modifiers,
+            } if key_modifiers.contains(&KeyModifier::Ctrl) => {
+                self.refresh_commits();
+                true
+            }
+            KeyWithModifier {
+                bare_key: BareKey::Char('c'),
+                key_modifiers,
+            } if key_modifiers.contains(&KeyModifier::Ctrl) => {
+                match self.commits_tab.commits.selected_branch_name() {
+                    Some(sha) => {
+                        self.name_prompt = Some(NamePrompt::new(NameAction::BranchAt { sha }));
+                        true
+                    }
+                    None => false,
+                }
+            }
+            KeyWithModifier {
+                bare_key: BareKey::Char('t'),
+                key_modifiers,
+            } if key_modifiers.contains(&KeyModifier::Ctrl) => {
+                match self.commits_tab.commits.selected_branch_name() {
+                    Some(sha) => {
+                        self.name_prompt = Some(NamePrompt::new(NameAction::TagAt { sha }));
+                        true
+                    }
+                    None => false,
+                }
+            }
+            KeyWithModifier {
+                bare_key: BareKey::Char('c'),
+                key_modifiers,
+            } if key_modifiers.contains(&KeyModifier::Alt) => {
+                match self.commits_tab.commits.selected_branch_name() {
+                    Some(sha) => {
+                        self.cherry_pick(vec![sha]);
+                        true
+                    }
+                    None => false,
+                }
+            }
+            KeyWithModifier {
+                bare_key: BareKey::Char('l'),
+                key_modifiers,
+            } if key_modifiers.contains(&KeyModifier::Ctrl) => {
+                match self.commits_tab.commits.selected_branch_name() {
+                    Some(sha) => {
+                        self.open_git_pane(vec!["show", &sha]);
+                        true
+                    }
+                    None => false,
+                }
+            }
+            _ => {
+                let handled = self.commits_tab.commits.handle_input_key(&key);
+                self.load_more_commits();
+                handled
+            }
+        }
+    }
+
+    fn handle_name_prompt_key_input(&mut self, key: KeyWithModifier) -> bool {
+        let Some(name_prompt) = &mut self.name_prompt else {
+            return false;
+        };
+        match key {
+            KeyWithModifier {
+                bare_key: BareKey::Esc,
+                ..
+            } => {
+                self.name_prompt = None;
+                true
+            }
+            KeyWithModifier {
+                bare_key: BareKey::Enter,
+                ..
+            } => {
+                let name = name_prompt.input.as_str().trim();
+                if name.is_empty() {
+                    return false;
+                }
+                let name = String::from(name);
+                let action = name_prompt.action.clone();
+                self.name_prompt = None;
+                match action {
+                    NameAction::BranchFromHead => self.create_branch(&name, None),
+                    NameAction::BranchAt { sha } => self.run_git(
+                        &["branch", &name, &sha],
+                        Command::CreateBranchAt {
+                            branch: name.clone(),
+                            sha: sha.clone(),
+                        },
+                    ),
+                    NameAction::TagAt { sha } => self.run_git(
+                        &["tag", &name, &sha],
+                        Command::Tag {
+                            tag: name.clone(),
+                            sha: sha.clone(),
+                        },
+                    ),
+                    NameAction::TrackAs { remote_branch } => {
+                        self.track_remote_branch_as(&remote_branch, &name)
+                    }
+                }
+                true
+            }
+            _ => name_prompt.input.handle_key(&key).is_some(),
+        }
+    }
+
+    fn handle_track_collision_key_input(&mut self, key: KeyWithModifier) -> bool {
+        let Some(track_collision) = self.track_collision.take() else {
+            return false;
+        };
+        let local_branch = self
+            .local_branches_tab
+            .view
+            .branches
+            .iter()
+            .find(|branch| branch.name == track_collision.local_branch)
+            .cloned();
+        match (key, local_branch) {
+            (
+                KeyWithModifier {
+                    bare_key: BareKey::Esc,
+                    ..
+                },
+                _,
+            ) => true,
+            (
+                KeyWithModifier {
+                    bare_key: BareKey::Char('s'),
+                    ..
+                },
+                Some(local_branch),
+            ) => {
+                self.branch_type = BranchType::Local;
+                self.local_branches_tab
+                    .select_branch_by_name(&local_branch.name);
+                self.switch_to_branch(&local_branch);
+                true
+            }
+            (
+                KeyWithModifier {
+                    bare_key: BareKey::Char('r'),
+                    ..
+                },
+                Some(local_branch),
+            ) => {
+                // The reset is confirmed from the local tab
+                self.branch_type = BranchType::Local;
+                self.local_branches_tab
+                    .select_branch_by_name(&local_branch.name);
+                self.reset_branch_to(&local_branch, &track_collision.remote_branch);
+                true
+            }
+            (
+                KeyWithModifier {
+                    bare_key: BareKey::Char('n'),
+                    ..
+                },
+                _,
+            ) => {
+                self.name_prompt = Some(NamePrompt::new(NameAction::TrackAs {
+                    remote_branch: track_collision.remote_branch,
+                }));
+                true
+            }
+            _ => {
+                self.track_collision = Some(track_collision);
+                false
+            }
+        }
+    }
+
+    fn handle_remotes_tab_key_input(&mut self, key: KeyWithModifier) -> bool {
+        match key {
+            KeyWithModifier {
+                bare_key: BareKey::Tab,
+                ..
+            } => {
+                self.branch_type = BranchType::Commits;
+                true
+            }
+            KeyWithModifier {
+                bare_key: BareKey::Down,
+                ..
+            } => {
+                self.remotes_tab.select_down();
+                true
+            }
+            KeyWithModifier {
+                bare_key: BareKey::Up,
+                ..
+            } => {
+                self.remotes_tab.select_up();
+                true
+            }
+            KeyWithModifier {
+                bare_key: BareKey::Char('r'),
+                key_modifiers,
+            } if key_modifiers.contains(&KeyModifier::Ctrl) => {
+                self.list_remotes();
+                true
+            }
+            KeyWithModifier {
+                bare_key: BareKey::Char('a'),
+                key_modifiers,
+            } if key_modifiers.contains(&KeyModifier::Ctrl) => {
+                if let Err(err) = self.add_remote() {
+                    self.messages.error(err.to_string(), "");
+                }
+                true
+            }
+            KeyWithModifier {
+                bare_key: BareKey::Char('e'),
+                key_modifiers,
+            } if key_modifiers.contains(&KeyModifier::Ctrl) => {
+                if self.remotes_tab.selected_remote().is_none() {
+                    return false;
+                }
+                if let Err(err) = self.rename_remote() {
+                    self.messages.error(err.to_string(), "");
+                }
+                true
+            }
+            KeyWithModifier {
+                bare_key: BareKey::Char('s'),
+                key_modifiers,
+            } if key_modifiers.contains(&KeyModifier::Ctrl) => {
+                if self.remotes_tab.selected_remote().is_none() {
+                    return false;
+                }
+                if let Err(err) = self.set_remote_url() {
+                    self.messages.error(err.to_string(), "");
+                }
+                true
+            }
+            KeyWithModifier {
+                bare_key: BareKey::Char('d'),
+                key_modifiers,
+            } if key_modifiers.contains(&KeyModifier::Ctrl) => {
+                match self.remotes_tab.selected_remote() {
+                    Some(remote) => {
+                        self.remotes_tab.removal = Some(remote.name.clone());
+                        true
+                    }
+                    None => false,
+                }
+            }
+            _ => self.remotes_tab.input.handle_key(&key).is_some(),
+        }
+    }
+
+    fn handle_remote_removal_key_input(&mut self, key: KeyWithModifier) -> bool {
+        match key {
             KeyWithModifier {
-                bare_key: BareKey::Char(c),
+                bare_key: BareKey::Esc,
                 ..
             } => {
-                self.remote_branches_tab.push_to_input(c);
+                self.remotes_tab.removal = None;
                 true
             }
             KeyWithModifier {
-                bare_key: BareKey::Backspace,
-                ..
-            } => {
-                self.remote_branches_tab.pop_from_input();
+                bare_key: BareKey::Char('y'),
+                key_modifiers,
+            } if key_modifiers.is_empty() => {
+                if let Some(remote) = self.remotes_tab.removal.take() {
+                    self.run_remote_command(
+                        &["remove", &remote],
+                        Command::RemoveRemote {
+                            remote: remote.clone(),
+                        },
+                    );
+                }
                 true
             }
             _ => false,
         }
     }
 
-    fn delete_branch(&self, branch_name: &str, force_delete: bool) {
-        let cmd = &[
-            "git",
-            "branch",
-            if force_delete { "-D" } else { "-d" },
-            branch_name,
-        ];
-        let context = BTreeMap::from([(String::from("command"), String::from("delete"))]);
-        match &self.cwd {
-            Some(cwd) => {
-                run_command_with_env_variables_and_cwd(cmd, BTreeMap::new(), cwd.clone(), context)
-            }
-            None => run_command(cmd, context),
+    fn add_remote(&mut self) -> Result<()> {
+        let input = String::from(self.remotes_tab.input.as_str());
+        let Some((name, url)) = input.trim().split_once(char::is_whitespace) else {
+            bail!("Enter the remote's name and URL separated by a space")
+        };
+        self.run_remote_command(
+            &["add", name, url.trim()],
+            Command::AddRemote {
+                remote: String::from(name),
+            },
+        );
+        Ok(())
+    }
+
+    fn rename_remote(&mut self) -> Result<()> {
+        let Some(remote) = self.remotes_tab.selected_remote().cloned() else {
+            bail!("No remote selected")
+        };
+        let new_name = String::from(self.remotes_tab.input.as_str().trim());
+        if new_name.is_empty() {
+            bail!("Enter the remote's new name")
         }
+        self.run_remote_command(
+            &["rename", &remote.name, &new_name],
+            Command::RenameRemote {
+                remote: remote.name.clone(),
+            },
+        );
+        Ok(())
     }
 
-    fn switch_to_previous_branch(&self) {
-        let cmd = &["git", "switch", "-"];
-        let context = BTreeMap::from([(String::from("command"), String::from("switch"))]);
-        match &self.cwd {
-            Some(cwd) => {
-                run_command_with_env_variables_and_cwd(cmd, BTreeMap::new(), cwd.clone(), context)
-            }
-            None => run_command(cmd, context),
+    fn set_remote_url(&mut self) -> Result<()> {
+        let Some(remote) = self.remotes_tab.selected_remote().cloned() else {
+            bail!("No remote selected")
+        };
+        let url = String::from(self.remotes_tab.input.as_str().trim());
+        if url.is_empty() {
+            bail!("Enter the remote's new URL")
+        }
+        self.run_remote_command(
+            &["set-url", &remote.name, &url],
+            Command::SetRemoteUrl {
+                remote: remote.name.clone(),
+            },
+        );
+        Ok(())
+    }
+
+    fn run_remote_command(&mut self, args: &[&str], command: Command) {
+        let mut remote_args = vec!["remote"];
+        remote_args.extend_from_slice(args);
+        self.run_git(&remote_args, command);
+    }
+
+    fn delete_branch(&mut self, branch_name: &str, force_delete: bool) {
+        self.run_git(
+            &[
+                "branch",
+                if force_delete { "-D" } else { "-d" },
+                branch_name,
+            ],
+            Command::Delete {
+                branch: String::from(branch_name),
+            },
+        );
+    }
+
+    /// Recreates the most recently deleted branch at its former tip
+    fn undo_delete(&mut self) -> bool {
+        let Some(deleted_branch) = self.deleted_branches.pop() else {
+            return false;
+        };
+        self.run_git(
+            &["branch", &deleted_branch.name, &deleted_branch.sha],
+            Command::RestoreBranch {
+                branch: deleted_branch.name.clone(),
+                sha: deleted_branch.sha.clone(),
+            },
+        );
+        true
+    }
+
+    fn fetch_all(&mut self, background: bool) {
+        if self.fetching {
+            return;
         }
+        self.fetching = true;
+        self.run_git(
+            &["fetch", "--all", "--prune"],
+            Command::FetchAll { background },
+        );
+    }
+
+    fn switch_to_previous_branch(&mut self) {
+        self.run_git(
+            &["switch", "-"],
+            Command::Switch {
+                branch: String::from("-"),
+            },
+        );
     }
 
-    fn fetch(&self, branch: &LocalBranch) -> Result<()> {
+    fn fetch(&mut self, branch: &LocalBranch) -> Result<()> {
         if let Some(upstream_info) = &branch.upstream_info {
             let Some((remote, remote_ref)) = upstream_info.name.split_once('/') else {
                 bail!("Invalid upstream")
@@ -378,212 +1768,854 @@ impl Git {
                     .unwrap_or(remote_ref),
                 branch.name
             );
-            let cmd = &["git", "fetch", remote, &refspec];
-            let context = BTreeMap::from([(String::from("command"), String::from("fetch"))]);
-            match &self.cwd {
-                Some(cwd) => run_command_with_env_variables_and_cwd(
-                    cmd,
-                    BTreeMap::new(),
-                    cwd.clone(),
-                    context,
-                ),
-                None => run_command(cmd, context),
-            }
+            self.run_git(
+                &["fetch", remote, &refspec],
+                Command::Fetch {
+                    branch: branch.name.clone(),
+                },
+            );
             Ok(())
         } else {
             bail!("Local branch does not track any remote branch")
         }
     }
 
-    fn switch_to_branch(&self, branch: &LocalBranch) {
-        match &self.cwd {
-            Some(cwd) => run_command_with_env_variables_and_cwd(
-                &["git", "switch", &branch.name],
-                BTreeMap::new(),
-                cwd.clone(),
-                BTreeMap::from([(String::from("command"), String::from("switch"))]),
-            ),
-            None => run_command(
-                &["git", "switch", &branch.name],
-                BTreeMap::from([(String::from("command"), String::from("switch"))]),
-            ),
+    fn open_upstream_picker(&mut self, branch_name: String) {
+        let mut upstream_picker = UpstreamPicker {
+            branch_name,
+            tab: Tab::default(),
+        };
+        upstream_picker.update_branches(&self.remote_branches_tab.view.branches);
+        self.upstream_picker = Some(upstream_picker);
+        if !self.remote_branches_tab.inited {
+            self.remote_branches_tab.inited = true;
+            self.list_remote_branches(false);
         }
     }
 
-    fn track_remote_branch(&self, remote_branch: &RemoteBranch) {
-        let command = &["git", "checkout", "--track", &remote_branch.name];
-        let context = BTreeMap::from([(String::from("command"), String::from("track_remote"))]);
-        match &self.cwd {
-            Some(cwd) => run_command_with_env_variables_and_cwd(
-                command,
-                BTreeMap::new(),
-                cwd.clone(),
-                context,
-            ),
-            None => run_command(command, context),
+    fn open_commit_picker(&mut self, branch_name: String) {
+        self.run_git(
+            &["log", Commit::LOG_FORMAT, &format!("HEAD..{branch_name}")],
+            Command::ListCommits {
+                branch: branch_name.clone(),
+            },
+        );
+        self.commit_picker = Some(CommitPicker {
+            branch_name,
+            tab: Tab::for_commits(),
+            marked: BTreeSet::new(),
+        });
+    }
+
+    fn cherry_pick(&mut self, commits: Vec<String>) {
+        let mut args = vec!["cherry-pick"];
+        args.extend(commits.iter().map(String::as_str));
+        self.run_git(
+            &args,
+            Command::CherryPick {
+                commits: commits.clone(),
+            },
+        );
+    }
+
+    fn set_upstream(&mut self, branch_name: &str, upstream: &str) {
+        self.run_git(
+            &[
+                "branch",
+                &format!("--set-upstream-to={upstream}"),
+                branch_name,
+            ],
+            Command::SetUpstream {
+                branch: String::from(branch_name),
+                upstream: String::from(upstream),
+            },
+        );
+    }
+
+    fn reset_current_branch_to(&mut self, target: &str) -> Result<()> {
+        let current_branch = self
+            .local_branches_tab
+            .view
+            .branches
+            .iter()
+            .find(|branch| branch.current)
+            .ok_or_else(|| anyhow!("No branch is checked out"))?;
+        if current_branch.rev() == target {
+            bail!("The current branch cannot be reset to itself")
         }
+        self.reset_prompt = Some(ResetPrompt {
+            branch: String::from(current_branch.rev()),
+            target: String::from(target),
+            mode: None,
+            state: ResetState::ChoosingMode,
+        });
+        Ok(())
     }
 
-    fn open_log_pane(&self, branch_name: impl AsRef<str>) {
-        let mut args = vec!["log"];
-        args.extend(self.log_args.iter().map(|arg| arg.as_str()));
-        args.push(branch_name.as_ref());
+    fn reset_to_upstream(&mut self, branch: &LocalBranch) -> Result<()> {
+        let Some(upstream_info) = &branch.upstream_info else {
+            bail!("Local branch does not track any remote branch")
+        };
+        self.reset_branch_to(branch, &upstream_info.name);
+        Ok(())
+    }
 
-        let mut command_to_run = CommandToRun::new_with_args("git", args);
-        command_to_run.cwd = self.cwd.clone();
-        if self.open_log_in_floating {
-            open_command_pane_floating(command_to_run, None, BTreeMap::new());
+    /// Resets the checked out branch with the chosen mode. Other branches are moved with
+    /// `git branch -f`, after checking that no commits would be lost.
+    fn reset_branch_to(&mut self, branch: &LocalBranch, target: &str) {
+        let reset_prompt = ResetPrompt {
+            branch: branch.name.clone(),
+            target: String::from(target),
+            mode: None,
+            state: if branch.current {
+                ResetState::ChoosingMode
+            } else {
+                ResetState::CountingCommits
+            },
+        };
+        if !branch.current {
+            self.count_lost_commits(&reset_prompt.branch, &reset_prompt.target);
+        }
+        self.reset_prompt = Some(reset_prompt);
+    }
+
+    fn count_lost_commits(&mut self, branch: &str, target: &str) {
+        self.run_git(
+            &["rev-list", "--count", &format!("{target}..{branch}")],
+            Command::CountLostCommits {
+                branch: String::from(branch),
+                target: String::from(target),
+            },
+        );
+    }
+
+    /// Asks for confirmation of the pending reset, unless it is a `git branch -f` that loses no
+    /// commits
+    fn confirm_reset(&mut self, branch: &str, target: &str, lost_commits: usize) {
+        let Some(reset_prompt) = self.reset_prompt.as_mut().filter(|reset_prompt| {
+            reset_prompt.state == ResetState::CountingCommits
+                && reset_prompt.branch == branch
+                && reset_prompt.target == target
+        }) else {
+            return;
+        };
+        if reset_prompt.mode.is_none() && lost_commits == 0 {
+            self.reset();
         } else {
-            open_command_pane(command_to_run, BTreeMap::new());
+            reset_prompt.state = ResetState::Confirming { lost_commits };
         }
     }
 
-    fn render_tab_bar(&self) {
-        let (local_text, remote_text) = match self.branch_type {
-            BranchType::Local => (
-                Text::new(Self::TEXT_LOCAL_TAB).selected(),
-                Text::new(Self::TEXT_REMOTE_TAB),
-            ),
-            BranchType::Remote => (
-                Text::new(Self::TEXT_LOCAL_TAB),
-                Text::new(Self::TEXT_REMOTE_TAB).selected(),
-            ),
+    fn reset(&mut self) {
+        let Some(reset_prompt) = self.reset_prompt.take() else {
+            return;
         };
+        let command = Command::Reset {
+            branch: reset_prompt.branch.clone(),
+            target: reset_prompt.target.clone(),
+            mode: reset_prompt.mode,
+        };
+        match reset_prompt.mode {
+            Some(mode) => self.run_git(&["reset", &mode.flag(), &reset_prompt.target], command),
+            None => self.run_git(
+                &["branch", "-f", &reset_prompt.branch, &reset_prompt.target],
+                command,
+            ),
+        }
+    }
 
-        print_ribbon_with_coordinates(local_text, 0, 0, None, None);
-        print_ribbon_with_coordinates(remote_text, Self::TEXT_LOCAL_TAB.len() + 4, 0, None, None);
+    fn unset_upstream(&mut self, branch: &LocalBranch) -> Result<()> {
+        if branch.upstream_info.is_none() {
+            bail!("Local branch does not track any remote branch")
+        }
+        self.run_git(
+            &["branch", "--unset-upstream", &branch.name],
+            Command::UnsetUpstream {
+                branch: branch.name.clone(),
+            },
+        );
+        Ok(())
     }
-}
 
-impl ZellijPlugin for Git {
-    fn load(&mut self, configuration: BTreeMap<String, String>) {
-        let plugin_ids = get_plugin_ids();
-        self.cwd = Some(plugin_ids.initial_cwd.clone());
-        self.open_log_in_floating = configuration
-            .get("open_log_in_floating")
-            .map(|value| value.parse::<bool>().unwrap_or(false))
-            .unwrap_or(false);
-        self.log_args = configuration
-            .get("log_args")
-            .map(|value| value.split(" ").map(String::from).collect())
-            .unwrap_or_default();
+    fn switch_to_branch(&mut self, branch: &LocalBranch) {
+        if branch.detached {
+            return;
+        }
+        self.run_git(
+            &["switch", &branch.name],
+            Command::Switch {
+                branch: branch.name.clone(),
+            },
+        );
+    }
 
-        subscribe(&[EventType::Key, EventType::RunCommandResult]);
-        request_permission(&[PermissionType::RunCommands]);
+    fn create_branch(&mut self, branch_name: &str, start: Option<&str>) {
+        let mut args = vec!["checkout", "-b", branch_name];
+        args.extend(start);
+        self.run_git(
+            &args,
+            Command::Create {
+                branch: String::from(branch_name),
+            },
+        );
     }
 
-    fn update(&mut self, event: Event) -> bool {
-        match event {
-            Event::RunCommandResult(Some(0), stdout, _stderr, context) => {
-                self.successful_command_update(context, stdout)
-            }
-            Event::RunCommandResult(Some(_err_code), _stdout, stderr, _context) => {
-                self.error_message = Some(String::from_utf8_lossy(&stderr).to_string());
-                true
-            }
-            Event::Key(key) => self.handle_key_input(key),
-            _ => false,
+    /// Tracks the remote branch with a new local branch, unless a local branch with the same name
+    /// exists. Then the user is asked what to do instead.
+    fn track_remote_branch(&mut self, remote_branch: &RemoteBranch) {
+        let tracked_name = remote_branch.tracked_name();
+        if self
+            .local_branches_tab
+            .view
+            .branches
+            .iter()
+            .any(|branch| !branch.detached && branch.name == remote_branch.local_name())
+        {
+            self.track_collision = Some(TrackCollision {
+                remote_branch: String::from(tracked_name),
+                local_branch: String::from(remote_branch.local_name()),
+            });
+            return;
         }
+        self.run_git(
+            &["checkout", "--track", tracked_name],
+            Command::TrackRemote {
+                branch: String::from(tracked_name),
+            },
+        );
     }
 
-    fn pipe(&mut self, pipe_message: PipeMessage) -> bool {
-        if pipe_message.name == "cwd" {
-            if let Some(payload) = pipe_message.payload {
-                let cwd = PathBuf::from(payload);
-                self.cwd = Some(cwd.clone());
-                self.list_local_branches();
-                self.list_remote_branches();
+    fn track_remote_branch_as(&mut self, remote_branch: &str, branch_name: &str) {
+        self.run_git(
+            &["checkout", "-b", branch_name, "--track", remote_branch],
+            Command::TrackRemote {
+                branch: String::from(remote_branch),
+            },
+        );
+    }
+
+    fn open_log_pane(&self, branch_name: impl AsRef<str>) {
+        let mut args = vec!["log"];
+        args.extend(self.log_args.iter().map(|arg| arg.as_str()));
+        args.push(branch_name.as_ref());
+        self.open_git_pane(args);
+    }
+
+    fn open_git_pane(&self, args: Vec<&str>) {
+        let mut command_to_run = CommandToRun::new_with_args("git", args);
+        command_to_run.cwd = self.cwd.clone();
+        self.host
+            .open_command_pane(command_to_run, self.open_log_in_floating);
+    }
+
+    /// Runs a command sent with `zellij pipe`. Commands sent from the command line get a reply once
+    /// their git command finished, other errors are displayed in the plugin.
+    fn handle_pipe_message(&mut self, pipe_message: PipeMessage) -> bool {
+        if !pipe_message.is_private && !PipeCommand::NAMES.contains(&pipe_message.name.as_str()) {
+            return false;
+        }
+        let generation = self.generation;
+        let result = PipeCommand::parse(&pipe_message.name, pipe_message.payload.as_deref())
+            .and_then(|command| self.run_pipe_command(command));
+        let pipe_id = match pipe_message.source {
+            PipeSource::Cli(pipe_id) => pipe_id,
+            PipeSource::Plugin(_) | PipeSource::Keybind => {
+                if let Err(err) = result {
+                    self.messages.error(err.to_string(), "");
+                }
                 return true;
             }
+        };
+        let started_command = self
+            .pending_commands
+            .range(generation + 1..)
+            .next_back()
+            .map(|(generation, _)| *generation);
+        match (result, started_command) {
+            (Err(err), _) => self
+                .host
+                .reply_to_cli_pipe(&pipe_id, &format!("Error: {err}\n")),
+            (Ok(status), None) => self.host.reply_to_cli_pipe(&pipe_id, &status),
+            (Ok(_), Some(generation)) => {
+                self.host.block_cli_pipe(&pipe_id);
+                self.cli_pipes.insert(generation, pipe_id);
+            }
         }
-        false
+        true
     }
 
-    fn render(&mut self, rows: usize, cols: usize) {
+    /// Runs the command the same way as its key binding. Returns what to reply when it doesn't
+    /// wait for a git command. Lists being reloaded are not waited for.
+    fn run_pipe_command(&mut self, command: PipeCommand) -> Result<String> {
+        if self.has_open_prompt() && !matches!(command, PipeCommand::Fetch | PipeCommand::Refresh) {
+            bail!("A prompt is open in the plugin, answer or cancel it first")
+        }
+        let status = match command {
+            PipeCommand::Cwd(cwd) => {
+                let status = format!("Changed the repository to {}\n", cwd.display());
+                self.change_repository(cwd);
+                status
+            }
+            PipeCommand::Switch { branch } if branch == "-" => {
+                self.switch_to_previous_branch();
+                String::new()
+            }
+            PipeCommand::Switch { branch } => {
+                if !self.local_branches_tab.inited {
+                    self.local_branches_tab.inited = true;
+                    self.list_local_branches(false);
+                    self.check_cherry_pick();
+                    bail!("Local branches are not loaded yet, try again")
+                }
+                let local_branch = self
+                    .local_branches_tab
+                    .view
+                    .branches
+                    .iter()
+                    .find(|local_branch| !local_branch.detached && local_branch.name == branch)
+                    .cloned()
+                    .ok_or_else(|| anyhow!("No local branch named {branch}"))?;
+                self.switch_to_branch(&local_branch);
+                String::new()
+            }
+            PipeCommand::Create { branch, start } => {
+                if !self.local_branches_tab.inited {
+                    self.local_branches_tab.inited = true;
+                    self.list_local_branches(false);
+                    self.check_cherry_pick();
+                    bail!("Local branches are not loaded yet, try again")
+                }
+                if self
+                    .local_branches_tab
+                    .view
+                    .branches
+                    .iter()
+                    .any(|local_branch| !local_branch.detached && local_branch.name == branch)
+                {
+                    bail!("A local branch named {branch} already exists")
+                }
+                self.create_branch(&branch, start.as_deref());
+                String::new()
+            }
+            PipeCommand::CheckoutRemote { branch } => {
+                if !self.remote_branches_tab.inited {
+                    self.remote_branches_tab.inited = true;
+                    self.list_remote_branches(false);
+                    bail!("Remote branches are not loaded yet, try again")
+                }
+                let remote_branch = self
+                    .remote_branches_tab
+                    .view
+                    .branches
+                    .iter()
+                    .find(|remote_branch| remote_branch.name == branch)
+                    .cloned()
+                    .ok_or_else(|| anyhow!("No remote branch named {branch}"))?;
+                self.track_remote_branch(&remote_branch);
+                if let Some(track_collision) = &self.track_collision {
+                    self.branch_type = BranchType::Remote;
+                    return Ok(format!(
+                        "{} already exists, choose what to do in the plugin\n",
+                        track_collision.local_branch
+                    ));
+                }
+                String::new()
+            }
+            PipeCommand::Fetch if self.fetching => bail!("Already fetching"),
+            PipeCommand::Fetch => {
+                self.fetch_all(false);
+                String::new()
+            }
+            PipeCommand::Refresh => {
+                self.refresh();
+                String::from("Reloading the lists\n")
+            }
+            PipeCommand::Tab(branch_type) => {
+                self.branch_type = branch_type;
+                format!(
+                    "Switched to the {} tab\n",
+                    PipeCommand::tab_name(branch_type)
+                )
+            }
+            PipeCommand::Filter(filter) => {
+                match self.branch_type {
+                    BranchType::Local => self.local_branches_tab.set_filter(&filter),
+                    BranchType::Remote => self.remote_branches_tab.set_filter(&filter),
+                    BranchType::Remotes => bail!("Remotes can't be filtered"),
+                    BranchType::Commits => {
+                        self.commits_tab.commits.set_filter(&filter);
+                        self.load_more_commits();
+                    }
+                }
+                if filter.is_empty() {
+                    String::from("Cleared the filter\n")
+                } else {
+                    format!("Filtering by {filter}\n")
+                }
+            }
+        };
+        Ok(status)
+    }
+
+    fn render_to(&mut self, canvas: &mut impl Canvas, rows: usize, cols: usize) {
         match self.branch_type {
             BranchType::Local => {
                 if !self.local_branches_tab.inited {
                     self.local_branches_tab.inited = true;
-                    self.list_local_branches();
+                    self.list_local_branches(false);
+                    self.check_cherry_pick();
                     return;
                 }
             }
             BranchType::Remote => {
                 if !self.remote_branches_tab.inited {
                     self.remote_branches_tab.inited = true;
-                    self.list_remote_branches();
+                    self.list_remote_branches(false);
+                    return;
+                }
+            }
+            BranchType::Remotes => {
+                if !self.remotes_tab.inited {
+                    self.remotes_tab.inited = true;
+                    self.list_remotes();
+                    return;
+                }
+            }
+            BranchType::Commits => {
+                if !self.commits_tab.commits.inited {
+                    self.commits_tab.commits.inited = true;
+                    self.list_log(0, CommitsTab::PAGE_SIZE);
                     return;
                 }
             }
         };
 
-        if let Some(message) = &self.error_message {
-            print_text_with_coordinates(Text::new("ERROR").color_range(2, ..), 0, 0, None, None);
-            for (y, line) in message.lines().enumerate() {
-                print_text_with_coordinates(Text::new(line), 0, y + 1, None, None);
-            }
+        const PADDING: usize = 1;
+        const TAB_BAR_HEIGHT: usize = 1;
+        const MIN_TABLE_HEIGHT: usize = 2;
+        const MIN_ROWS: usize = TAB_BAR_HEIGHT + PADDING + 1 + PADDING + MIN_TABLE_HEIGHT;
+        const MIN_COLS: usize = 30;
+
+        if rows < MIN_ROWS || cols < MIN_COLS {
+            canvas.print_text(&Span::new(Self::TEXT_PANE_TOO_SMALL).fit(cols), 0, 0);
             return;
         }
 
-        const PADDING: usize = 1;
-        const FOOTER_HEIGHT: usize = 2;
-        const TAB_BAR_HEIGHT: usize = 1;
+        let cherry_pick_help;
+        let help = match self.branch_type {
+            _ if self.messages.is_open() => Messages::HELP,
+            _ if self.track_collision.is_some() => TrackCollision::HELP,
+            _ if self.name_prompt.is_some() => NamePrompt::HELP,
+            BranchType::Local if let Some(reset_prompt) = &self.reset_prompt => reset_prompt.help(),
+            BranchType::Local if self.upstream_picker.is_some() => Tab::<RemoteBranch>::PICKER_HELP,
+            BranchType::Local if self.commit_picker.is_some() => Tab::<Commit>::PICKER_HELP,
+            BranchType::Local if self.cherry_pick_conflict => Tab::<Commit>::CONFLICT_HELP,
+            BranchType::Local if self.cherry_pick_in_progress => {
+                cherry_pick_help = Tab::<LocalBranch>::cherry_pick_help();
+                &cherry_pick_help
+            }
+            BranchType::Local => Tab::<LocalBranch>::HELP,
+            BranchType::Remote => Tab::<RemoteBranch>::HELP,
+            BranchType::Remotes if self.remotes_tab.removal.is_some() => RemotesTab::REMOVAL_HELP,
+            BranchType::Remotes => RemotesTab::HELP,
+            BranchType::Commits => CommitsTab::HELP,
+        };
+        let help_lines = tab::help_lines(help, cols);
 
-        self.render_tab_bar();
         let input_rect = Rect {
             x: PADDING,
             y: PADDING + TAB_BAR_HEIGHT,
-            width: cols - (2 * PADDING),
+            width: cols - 2 * PADDING,
             height: 1,
         };
         let table_y = input_rect.y + input_rect.height + PADDING;
+
+        // The message panel may take up to half of the space below the input, it scrolls when its
+        // content doesn't fit.
+        let area_height = rows - table_y;
+        let panel_height = self.messages.height().min(area_height / 2);
+
+        // The footer gives up its lines when the pane gets short: the help goes first, then the
+        // current directory. Notices are rare and usually need action, so they are kept longest,
+        // followed by the status of the last action.
+        let mut spare_rows = (area_height - panel_height).saturating_sub(MIN_TABLE_HEIGHT);
+        let mut reserve = |height: usize| {
+            let fits = height <= spare_rows;
+            if fits {
+                spare_rows -= height;
+            }
+            fits
+        };
+        let show_notice = self.notice.is_some() && reserve(1);
+        let status = self.messages.status().filter(|_| reserve(1));
+        let show_cwd = reserve(1);
+        let show_help = reserve(help_lines.len());
+        let footer_height = usize::from(show_notice)
+            + usize::from(status.is_some())
+            + usize::from(show_cwd)
+            + if show_help { help_lines.len() } else { 0 };
+        let footer_padding = usize::from(footer_height > 0 && spare_rows > 0) * PADDING;
+
         let table_rect = Rect {
             x: PADDING,
             y: table_y,
-            width: cols - (2 * PADDING),
-            height: rows - table_y - PADDING - FOOTER_HEIGHT,
+            width: cols - 2 * PADDING,
+            height: area_height - panel_height - footer_height - footer_padding,
         };
+        let panel_rect = Rect {
+            y: table_rect.y + table_rect.height,
+            height: panel_height,
+            ..table_rect
+        };
+
+        self.render_tab_bar(canvas, cols);
         match self.branch_type {
-            BranchType::Local => {
-                print_text_with_coordinates(
-                    Text::new(format!(
-                        "Branch: {}|",
-                        self.local_branches_tab.input.clone()
-                    )),
+            BranchType::Local if self.upstream_picker.is_some() => {
+                let Some(upstream_picker) = &mut self.upstream_picker else {
+                    return;
+                };
+                canvas.print_text(
+                    &upstream_picker.tab.input.to_span(
+                        &format!("Upstream of {}: ", upstream_picker.branch_name),
+                        input_rect.width,
+                    ),
                     input_rect.x,
                     input_rect.y,
-                    Some(input_rect.width),
-                    Some(input_rect.height),
                 );
-                self.local_branches_tab.render_branch_list(table_rect);
-                self.local_branches_tab.render_help(rows);
+                upstream_picker.tab.render_branch_list(canvas, table_rect);
             }
-            BranchType::Remote => {
-                print_text_with_coordinates(
-                    Text::new(format!(
-                        "Branch: {}|",
-                        self.remote_branches_tab.input.clone()
-                    )),
+            BranchType::Local if self.commit_picker.is_some() => {
+                let Some(commit_picker) = &mut self.commit_picker else {
+                    return;
+                };
+                canvas.print_text(
+                    &commit_picker.tab.input.to_span(
+                        &format!("Cherry-pick from {}: ", commit_picker.branch_name),
+                        input_rect.width,
+                    ),
                     input_rect.x,
                     input_rect.y,
-                    Some(input_rect.width),
-                    Some(input_rect.height),
                 );
-                self.remote_branches_tab.render_branch_list(table_rect);
-                self.remote_branches_tab.render_help(rows);
+                commit_picker
+                    .tab
+                    .render_commit_list(canvas, table_rect, &commit_picker.marked);
+            }
+            BranchType::Local => {
+                let input = match &self.reset_prompt {
+                    Some(reset_prompt) => reset_prompt.to_span(input_rect.width),
+                    None if self.cherry_pick_conflict => Span::new(Self::TEXT_CHERRY_PICK_CONFLICT)
+                        .color_range(2, ..)
+                        .fit(input_rect.width),
+                    None if let Some(name_prompt) = &self.name_prompt => name_prompt
+                        .input
+                        .to_span(&name_prompt.prompt(), input_rect.width),
+                    None => self
+                        .local_branches_tab
+                        .input
+                        .to_span("Branch: ", input_rect.width),
+                };
+                canvas.print_text(&input, input_rect.x, input_rect.y);
+                self.local_branches_tab
+                    .render_branch_list(canvas, table_rect);
+            }
+            BranchType::Remote => {
+                let scope = self
+                    .remote_branches_tab
+                    .scope
+                    .as_ref()
+                    .map(|scope| format!(" [{scope}]"))
+                    .unwrap_or_default();
+                let input = match (&self.track_collision, &self.name_prompt) {
+                    (Some(track_collision), _) => track_collision.to_span(input_rect.width),
+                    (None, Some(name_prompt)) => name_prompt
+                        .input
+                        .to_span(&name_prompt.prompt(), input_rect.width),
+                    (None, None) => self
+                        .remote_branches_tab
+                        .input
+                        .to_span(&format!("Branch{scope}: "), input_rect.width),
+                };
+                canvas.print_text(&input, input_rect.x, input_rect.y);
+                self.remote_branches_tab
+                    .render_branch_list(canvas, table_rect);
             }
+            BranchType::Remotes => {
+                let input = match &self.remotes_tab.removal {
+                    Some(remote) => Span::new(format!("Remove remote {remote}?"))
+                        .color_range(2, ..)
+                        .fit(input_rect.width),
+                    None => self.remotes_tab.input.to_span("Remote: ", input_rect.width),
+                };
+                canvas.print_text(&input, input_rect.x, input_rect.y);
+                self.remotes_tab.render_remote_list(canvas, table_rect);
+            }
+            BranchType::Commits => {
+                let input = match &self.name_prompt {
+                    Some(name_prompt) => name_prompt
+                        .input
+                        .to_span(&name_prompt.prompt(), input_rect.width),
+                    None => self
+                        .commits_tab
+                        .commits
+                        .input
+                        .to_span("Commit: ", input_rect.width),
+                };
+                canvas.print_text(&input, input_rect.x, input_rect.y);
+                self.commits_tab.render_commit_list(canvas, table_rect);
+            }
+        }
+
+        self.messages.render(canvas, panel_rect);
+
+        let mut y = panel_rect.y + panel_rect.height + footer_padding;
+        if let Some(notice) = self.notice.as_ref().filter(|_| show_notice) {
+            canvas.print_text(
+                &Span::new(format!("{notice} (<Esc> to dismiss)"))
+                    .color_range(1, ..)
+                    .fit(cols),
+                0,
+                y,
+            );
+            y += 1;
+        }
+        if let Some(status) = status {
+            canvas.print_text(&status.fit(cols), 0, y);
+            y += 1;
         }
+        if show_help {
+            for line in help_lines {
+                canvas.print_text(&line.fit(cols), 0, y);
+                y += 1;
+            }
+        }
+        if show_cwd {
+            let cwd = self
+                .cwd
+                .as_ref()
+                .map(|cwd| cwd.to_string_lossy().to_string())
+                .unwrap_or_default();
+            self.displayed_fetch_minutes = self.minutes_since_fetch();
+            let footer = match self.displayed_fetch_minutes {
+                _ if self.fetch_failed => format!("{cwd}  (fetch failed, <Alt-m> for details)"),
+                Some(minutes) => format!("{cwd}  (last fetched {minutes} min ago)"),
+                None => cwd,
+            };
+            canvas.print_text(&Span::new(footer).fit(cols), 0, y);
+        }
+    }
+
+    fn render_tab_bar(&self, canvas: &mut impl Canvas, cols: usize) {
+        let tabs = [
+            (BranchType::Local, Self::TEXT_LOCAL_TAB),
+            (BranchType::Remote, Self::TEXT_REMOTE_TAB),
+            (BranchType::Remotes, Self::TEXT_REMOTES_TAB),
+            (BranchType::Commits, Self::TEXT_COMMITS_TAB),
+        ];
 
-        if let Some(cwd) = &self.cwd {
-            print_text_with_coordinates(
-                Text::new(cwd.to_string_lossy().to_string()),
+        let mut x = 0;
+        for (branch_type, text) in tabs {
+            let ribbon = if branch_type == self.branch_type {
+                Span::new(text).selected()
+            } else {
+                Span::new(text)
+            };
+            canvas.print_ribbon(&ribbon, x, 0);
+            x += text.len() + 4;
+        }
+        if !self.pending_commands.is_empty() {
+            let spinner = format!(
+                "{} {}",
+                Self::SPINNER_FRAMES[self.spinner_frame],
+                self.pending_commands
+                    .values()
+                    .map(String::as_str)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+            canvas.print_text(
+                &Span::new(spinner)
+                    .color_range(1, ..)
+                    .fit(cols.saturating_sub(x + 1)),
+                x + 1,
                 0,
-                rows - 1,
-                None,
-                None,
             );
         }
     }
 }
 
+impl<R: GitRunner + Default, H: Host + Default> ZellijPlugin for Git<R, H> {
+    fn load(&mut self, configuration: BTreeMap<String, String>) {
+        let plugin_ids = get_plugin_ids();
+        self.cwd = Some(plugin_ids.initial_cwd.clone());
+        self.open_log_in_floating = configuration
+            .get("open_log_in_floating")
+            .map(|value| value.parse::<bool>().unwrap_or(false))
+            .unwrap_or(false);
+        self.log_args = configuration
+            .get("log_args")
+            .map(|value| value.split(" ").map(String::from).collect())
+            .unwrap_or_default();
+        self.fetch_interval = configuration
+            .get("fetch_interval")
+            .and_then(|value| value.parse::<u64>().ok())
+            .filter(|seconds| *seconds > 0)
+            .map(Duration::from_secs);
+        if let Some(filter_fields) = configuration.get("filter_fields") {
+            match filter_fields
+                .split([' ', ','])
+                .filter(|key| !key.is_empty())
+                .map(str::parse::<SearchField>)
+                .collect::<Result<Vec<_>>>()
+            {
+                Ok(search_fields) => {
+                    self.local_branches_tab.search_fields = search_fields.clone();
+                    self.remote_branches_tab.search_fields = search_fields;
+                }
+                Err(err) => self.messages.error(err.to_string(), ""),
+            }
+        }
+
+        subscribe(&[
+            EventType::Key,
+            EventType::RunCommandResult,
+            EventType::FileSystemCreate,
+            EventType::FileSystemUpdate,
+            EventType::FileSystemDelete,
+            EventType::Timer,
+            EventType::PastedText,
+        ]);
+        request_permission(&[
+            PermissionType::RunCommands,
+            PermissionType::FullHdAccess,
+            PermissionType::ReadCliPipes,
+        ]);
+        watch_filesystem();
+        self.schedule_fetch();
+    }
+
+    fn update(&mut self, event: Event) -> bool {
+        match event {
+            Event::RunCommandResult(exit_code, stdout, stderr, context) => {
+                let context = match CommandContext::try_from(&context) {
+                    Ok(context) => context,
+                    Err(err) => {
+                        self.messages.error(
+                            "Failed to read the context of a git command",
+                            err.to_string(),
+                        );
+                        return true;
+                    }
+                };
+                self.pending_commands.remove(&context.generation);
+                if self.is_superseded(&context) {
+                    return false;
+                }
+                if let Some(pipe_id) = self.cli_pipes.remove(&context.generation) {
+                    let reply = match exit_code {
+                        Some(0) => success_status(&context.command, &stdout).unwrap_or_else(|| {
+                            format!("Finished {}", context.command.description())
+                        }),
+                        Some(exit_code) => format!(
+                            "Error: {} failed with exit code {exit_code}\n{}",
+                            context.command.description(),
+                            String::from_utf8_lossy(&stderr).trim_end()
+                        ),
+                        None => format!("Error: {} was interrupted", context.command.description()),
+                    };
+                    self.host
+                        .reply_to_cli_pipe(&pipe_id, &format!("{}\n", reply.trim_end()));
+                }
+                match exit_code {
+                    Some(0) => self.successful_command_update(context, stdout, stderr),
+                    exit_code => {
+                        self.failed_command_update(context, exit_code, stderr);
+                        true
+                    }
+                }
+            }
+            Event::Key(key) => self.handle_key_input(key),
+            Event::FileSystemCreate(paths)
+            | Event::FileSystemUpdate(paths)
+            | Event::FileSystemDelete(paths) => self.handle_file_system_event(&paths),
+            Event::Timer(_) => self.handle_timer(),
+            Event::PastedText(text) => self.handle_paste(&text),
+            _ => false,
+        }
+    }
+
+    fn pipe(&mut self, pipe_message: PipeMessage) -> bool {
+        self.handle_pipe_message(pipe_message)
+    }
+
+    fn render(&mut self, rows: usize, cols: usize) {
+        self.render_to(&mut ZellijCanvas, rows, cols);
+    }
+}
+
+fn success_status(command: &Command, stdout: &[u8]) -> Option<String> {
+    let status = match command {
+        Command::ListLocalBranches { .. }
+        | Command::ListRemoteBranches { .. }
+        | Command::ListRemotes
+        | Command::CheckCherryPick
+        | Command::FetchAll { background: true }
+        | Command::CountLostCommits { .. }
+        | Command::ListCommits { .. }
+        | Command::ListLog { .. } => return None,
+        Command::Switch { branch } if branch == "-" => {
+            String::from("Switched to the previous branch")
+        }
+        Command::Switch { branch } => format!("Switched to {branch}"),
+        Command::Create { branch } => format!("Created and switched to {branch}"),
+        Command::Delete { branch } => match stdout
+            .lines()
+            .map_while(Result::ok)
+            .find_map(|line| parse_deleted_branch(&line))
+        {
+            Some((name, sha)) => format!("Deleted {name} (was {sha})"),
+            None => format!("Deleted {branch}"),
+        },
+        Command::RestoreBranch { branch, sha } => format!("Restored {branch} at {sha}"),
+        Command::Fetch { branch } => format!("Fetched {branch}"),
+        Command::FetchAll { background: false } => String::from("Fetched all remotes"),
+        Command::TrackRemote { branch } => format!("Switched to a new branch tracking {branch}"),
+        Command::SetUpstream { branch, upstream } => {
+            format!("Set upstream of {branch} to {upstream}")
+        }
+        Command::UnsetUpstream { branch } => format!("Unset upstream of {branch}"),
+        Command::Reset {
+            branch,
+            target,
+            mode: Some(mode),
+        } => format!("Reset {branch} to {target} ({mode})"),
+        Command::Reset { branch, target, .. } => format!("Reset {branch} to {target}"),
+        Command::CherryPick { commits } if commits.len() == 1 => {
+            String::from("Cherry-picked 1 commit")
+        }
+        Command::CherryPick { commits } => format!("Cherry-picked {} commits", commits.len()),
+        Command::CherryPickContinue => String::from("Continued the cherry-pick"),
+        Command::CherryPickSkip => String::from("Skipped the commit"),
+        Command::CherryPickAbort => String::from("Aborted the cherry-pick"),
+        Command::CheckoutDetached { sha } => format!("Checked out {sha}"),
+        Command::CreateBranchAt { branch, sha } => format!("Created {branch} at {sha}"),
+        Command::Tag { tag, sha } => format!("Tagged {sha} as {tag}"),
+        Command::AddRemote { remote } => format!("Added remote {remote}"),
+        Command::RenameRemote { remote } => format!("Renamed remote {remote}"),
+        Command::RemoveRemote { remote } => format!("Removed remote {remote}"),
+        Command::SetRemoteUrl { remote } => format!("Changed the URL of {remote}"),
+        Command::SetRemoteHead { remote } => format!("Updated the default branch of {remote}"),
+    };
+    Some(status)
+}
+
+fn is_repository_state_path(path: &Path) -> bool {
+    let mut components = path.components().map(|component| component.as_os_str());
+    components.by_ref().any(|component| component == ".git")
+        && components.next().is_some_and(|component| {
+            ["HEAD", "refs", "packed-refs", "CHERRY_PICK_HEAD"]
+                .contains(&component.to_str().unwrap_or_default())
+        })
+}
+
+#[cfg(not(test))]
 register_plugin!(Git);
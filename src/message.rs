@@ -0,0 +1,196 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    canvas::{Canvas, Rect, Span},
+    tab::HelpEntry,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Level {
+    Error,
+    Info,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Message {
+    pub level: Level,
+    pub title: String,
+    pub body: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Panel {
+    Latest,
+    History,
+}
+
+/// Recent messages and the panel displaying them below the branch list
+#[derive(Default)]
+pub struct Messages {
+    history: VecDeque<Message>,
+    panel: Option<Panel>,
+    scroll: usize,
+    page_height: usize,
+    status_expires: Option<Instant>,
+}
+
+impl Messages {
+    const HISTORY_LIMIT: usize = 20;
+    const TEXT_HISTORY: &'static str = "Messages";
+    const TEXT_NO_MESSAGES: &'static str = "No messages yet";
+    const TEXT_DETAILS: &'static str = " (<Alt-m> for details)";
+    pub const STATUS_SECONDS: f64 = 5.0;
+
+    pub const HELP: &'static [HelpEntry] = &[
+        ("<Esc>", "Dismiss"),
+        ("<PgUp>", "Scroll up"),
+        ("<PgDn>", "Scroll down"),
+        ("<Alt-m>", "All messages"),
+    ];
+
+    pub fn error(&mut self, title: impl Into<String>, body: impl Into<String>) {
+        self.push(Message {
+            level: Level::Error,
+            title: title.into(),
+            body: body.into(),
+        });
+        self.panel = Some(Panel::Latest);
+        self.scroll = 0;
+        self.status_expires = None;
+    }
+
+    /// Records a message about a successful action and shows its title in the status line for
+    /// [`Self::STATUS_SECONDS`]
+    pub fn info(&mut self, title: impl Into<String>, body: impl Into<String>) {
+        self.push(Message {
+            level: Level::Info,
+            title: title.into(),
+            body: body.into(),
+        });
+        self.status_expires = Some(Instant::now() + Duration::from_secs_f64(Self::STATUS_SECONDS));
+    }
+
+    pub fn push(&mut self, message: Message) {
+        self.history.push_front(message);
+        self.history.truncate(Self::HISTORY_LIMIT);
+    }
+
+    pub fn latest(&self) -> Option<&Message> {
+        self.history.front()
+    }
+
+    pub fn status(&self) -> Option<Span> {
+        self.status_expires?;
+        let message = self.latest()?;
+        let mut status = message.title.clone();
+        if !message.body.is_empty() {
+            status.push_str(Self::TEXT_DETAILS);
+        }
+        Some(Span::new(status).color_range(1, ..message.title.chars().count()))
+    }
+
+    /// Clears the status line once it has been shown long enough, returns whether it was cleared
+    pub fn expire_status(&mut self, now: Instant) -> bool {
+        let expired = self.status_expires.is_some_and(|expires| now >= expires);
+        if expired {
+            self.status_expires = None;
+        }
+        expired
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.panel.is_some()
+    }
+
+    pub fn dismiss(&mut self) {
+        self.panel = None;
+        self.scroll = 0;
+    }
+
+    pub fn toggle_history(&mut self) {
+        self.panel = match self.panel {
+            Some(Panel::History) => None,
+            _ => Some(Panel::History),
+        };
+        self.scroll = 0;
+    }
+
+    pub fn page_up(&mut self) {
+        self.scroll = self.scroll.saturating_sub(self.page_height.max(1));
+    }
+
+    pub fn page_down(&mut self) {
+        let max_scroll = self.lines().len().saturating_sub(self.page_height);
+        self.scroll = (self.scroll + self.page_height.max(1)).min(max_scroll);
+    }
+
+    /// Rows needed to display the open panel without scrolling
+    pub fn height(&self) -> usize {
+        match self.panel {
+            Some(_) => 1 + self.lines().len(),
+            None => 0,
+        }
+    }
+
+    pub fn render(&mut self, canvas: &mut impl Canvas, rect: Rect) {
+        if self.panel.is_none() || rect.height == 0 {
+            return;
+        }
+        let lines = self.lines();
+        let visible_lines = rect.height - 1;
+        self.page_height = visible_lines;
+        self.scroll = self.scroll.min(lines.len().saturating_sub(visible_lines));
+
+        let position = if lines.len() > visible_lines {
+            format!(
+                " {}-{}/{} ",
+                self.scroll + 1,
+                self.scroll + visible_lines,
+                lines.len()
+            )
+        } else {
+            String::new()
+        };
+        let rule = format!("──{position}{}", "─".repeat(rect.width));
+        canvas.print_text(&Span::new(rule).fit(rect.width), rect.x, rect.y);
+        for (y, line) in lines
+            .into_iter()
+            .skip(self.scroll)
+            .take(visible_lines)
+            .enumerate()
+        {
+            canvas.print_text(&line.fit(rect.width), rect.x, rect.y + 1 + y);
+        }
+    }
+
+    fn lines(&self) -> Vec<Span> {
+        match self.panel {
+            Some(Panel::Latest) => self.latest().map(message_lines).unwrap_or_default(),
+            Some(Panel::History) if self.history.is_empty() => {
+                vec![Span::new(Self::TEXT_NO_MESSAGES)]
+            }
+            Some(Panel::History) => {
+                let mut lines = vec![Span::new(Self::TEXT_HISTORY).color_range(0, ..)];
+                for message in &self.history {
+                    lines.push(Span::default());
+                    lines.extend(message_lines(message));
+                }
+                lines
+            }
+            None => Vec::new(),
+        }
+    }
+}
+
+fn message_lines(message: &Message) -> Vec<Span> {
+    let title_color = match message.level {
+        Level::Error => 2,
+        Level::Info => 1,
+    };
+    std::iter::once(Span::new(&message.title).color_range(title_color, ..))
+        .chain(message.body.lines().map(Span::new))
+        .collect()
+}
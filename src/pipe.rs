@@ -0,0 +1,105 @@
+use std::path::PathBuf;
+
+use anyhow::{anyhow, bail};
+
+use crate::BranchType;
+
+/// A command sent to the plugin with `zellij pipe`. The name of the pipe message is the command,
+/// its payload holds the arguments.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PipeCommand {
+    Cwd(PathBuf),
+    Switch {
+        branch: String,
+    },
+    Create {
+        branch: String,
+        start: Option<String>,
+    },
+    CheckoutRemote {
+        branch: String,
+    },
+    Fetch,
+    Refresh,
+    Tab(BranchType),
+    Filter(String),
+}
+
+impl PipeCommand {
+    /// Names of the commands. Messages with other names are meant for other plugins, unless they
+    /// are sent to this plugin only.
+    pub const NAMES: [&'static str; 8] = [
+        "cwd",
+        "switch",
+        "create",
+        "checkout-remote",
+        "fetch",
+        "refresh",
+        "tab",
+        "filter",
+    ];
+
+    const TABS: [(&'static str, BranchType); 4] = [
+        ("local", BranchType::Local),
+        ("remote", BranchType::Remote),
+        ("remotes", BranchType::Remotes),
+        ("commits", BranchType::Commits),
+    ];
+
+    pub fn parse(name: &str, payload: Option<&str>) -> anyhow::Result<Self> {
+        let payload = payload.unwrap_or_default();
+        let args: Vec<&str> = payload.split_whitespace().collect();
+        let command = match (name, args.as_slice()) {
+            ("cwd", _) if !payload.trim().is_empty() => {
+                PipeCommand::Cwd(PathBuf::from(payload.trim()))
+            }
+            ("cwd", _) => bail!("Usage: cwd <path>"),
+            ("switch", [branch]) => PipeCommand::Switch {
+                branch: String::from(*branch),
+            },
+            ("switch", _) => bail!("Usage: switch <branch>"),
+            ("create", [branch]) => PipeCommand::Create {
+                branch: String::from(*branch),
+                start: None,
+            },
+            ("create", [branch, start]) => PipeCommand::Create {
+                branch: String::from(*branch),
+                start: Some(String::from(*start)),
+            },
+            ("create", _) => bail!("Usage: create <name> [start]"),
+            ("checkout-remote", [branch]) if branch.contains('/') => PipeCommand::CheckoutRemote {
+                branch: String::from(*branch),
+            },
+            ("checkout-remote", _) => bail!("Usage: checkout-remote <remote/branch>"),
+            ("fetch", []) => PipeCommand::Fetch,
+            ("fetch", _) => bail!("Usage: fetch"),
+            ("refresh", []) => PipeCommand::Refresh,
+            ("refresh", _) => bail!("Usage: refresh"),
+            ("tab", [tab]) => PipeCommand::Tab(Self::parse_tab(tab)?),
+            ("tab", _) => bail!("Usage: tab {}", Self::tab_names()),
+            ("filter", _) => PipeCommand::Filter(String::from(payload.trim())),
+            _ => bail!("Unknown command: {name}"),
+        };
+        Ok(command)
+    }
+
+    fn parse_tab(tab: &str) -> anyhow::Result<BranchType> {
+        Self::TABS
+            .into_iter()
+            .find(|(name, _)| *name == tab)
+            .map(|(_, branch_type)| branch_type)
+            .ok_or_else(|| anyhow!("Unknown tab: {tab}. Expected {}", Self::tab_names()))
+    }
+
+    /// The name the tab is selected by
+    pub fn tab_name(branch_type: BranchType) -> &'static str {
+        Self::TABS
+            .into_iter()
+            .find(|(_, tab)| *tab == branch_type)
+            .map_or("", |(name, _)| name)
+    }
+
+    fn tab_names() -> String {
+        Self::TABS.map(|(name, _)| name).join("|")
+    }
+}
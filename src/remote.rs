@@ -0,0 +1,77 @@
+use nom::{
+    AsChar, IResult, Parser,
+    branch::alt,
+    bytes::complete::{tag, take_till1},
+    combinator::value,
+    error::context,
+    sequence::delimited,
+};
+
+use anyhow::anyhow;
+
+use crate::branch::ws;
+
+#[derive(Clone, Copy, PartialEq)]
+enum UrlKind {
+    Fetch,
+    Push,
+}
+
+fn parse_remote_name(value: &str) -> IResult<&str, &str> {
+    context("remote_name", take_till1(AsChar::is_space)).parse(value)
+}
+
+fn parse_url(value: &str) -> IResult<&str, &str> {
+    context("url", take_till1(AsChar::is_space)).parse(value)
+}
+
+fn parse_url_kind(input: &str) -> IResult<&str, UrlKind> {
+    context(
+        "url_kind",
+        delimited(
+            tag("("),
+            alt((
+                value(UrlKind::Fetch, tag("fetch")),
+                value(UrlKind::Push, tag("push")),
+            )),
+            tag(")"),
+        ),
+    )
+    .parse(input)
+}
+
+#[derive(Clone)]
+pub struct Remote {
+    pub name: String,
+    pub fetch_url: String,
+    pub push_url: Option<String>,
+}
+
+impl Remote {
+    pub fn parse_list(output: &str) -> anyhow::Result<Vec<Remote>> {
+        let mut remotes: Vec<Remote> = Vec::new();
+        for line in output.lines().filter(|line| !line.trim().is_empty()) {
+            let (name, url, url_kind) = (ws(parse_remote_name), ws(parse_url), parse_url_kind)
+                .parse(line)
+                .map_err(|e| anyhow!("Failed to parse remote line: {}", e.to_owned()))?
+                .1;
+
+            let remote = match remotes.iter_mut().find(|remote| remote.name == name) {
+                Some(remote) => remote,
+                None => {
+                    remotes.push(Remote {
+                        name: String::from(name),
+                        fetch_url: String::new(),
+                        push_url: None,
+                    });
+                    remotes.last_mut().expect("remote was just pushed")
+                }
+            };
+            match url_kind {
+                UrlKind::Fetch => remote.fetch_url = String::from(url),
+                UrlKind::Push => remote.push_url = Some(String::from(url)),
+            }
+        }
+        Ok(remotes)
+    }
+}
@@ -0,0 +1,108 @@
+use std::{fmt::Display, str::FromStr};
+
+use anyhow::anyhow;
+
+use crate::{canvas::Span, tab::HelpEntry};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ResetMode {
+    Soft,
+    Mixed,
+    Hard,
+}
+
+impl ResetMode {
+    pub const ALL: [ResetMode; 3] = [ResetMode::Soft, ResetMode::Mixed, ResetMode::Hard];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ResetMode::Soft => "soft",
+            ResetMode::Mixed => "mixed",
+            ResetMode::Hard => "hard",
+        }
+    }
+
+    pub fn flag(&self) -> String {
+        format!("--{}", self.as_str())
+    }
+
+    fn key(&self) -> char {
+        self.as_str().chars().next().unwrap_or_default()
+    }
+}
+
+impl Display for ResetMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ResetMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ResetMode::ALL
+            .into_iter()
+            .find(|mode| mode.as_str() == s)
+            .ok_or_else(|| anyhow!("Unknown reset mode: {s}"))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ResetState {
+    ChoosingMode,
+    CountingCommits,
+    Confirming { lost_commits: usize },
+}
+
+/// A reset waiting for the user to pick a mode or to confirm that commits will be lost. When
+/// `mode` is `None`, the branch is not checked out and is moved with `git branch -f`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResetPrompt {
+    pub branch: String,
+    pub target: String,
+    pub mode: Option<ResetMode>,
+    pub state: ResetState,
+}
+
+impl ResetPrompt {
+    const MODE_HELP: &'static [HelpEntry] = &[
+        ("s", "Soft"),
+        ("m", "Mixed"),
+        ("h", "Hard"),
+        ("<Esc>", "Cancel"),
+    ];
+    const CONFIRM_HELP: &'static [HelpEntry] = &[("y", "Reset"), ("<Esc>", "Cancel")];
+    const COUNTING_HELP: &'static [HelpEntry] = &[("<Esc>", "Cancel")];
+
+    pub fn help(&self) -> &'static [HelpEntry] {
+        match self.state {
+            ResetState::ChoosingMode => Self::MODE_HELP,
+            ResetState::CountingCommits => Self::COUNTING_HELP,
+            ResetState::Confirming { .. } => Self::CONFIRM_HELP,
+        }
+    }
+
+    pub fn mode_for_key(c: char) -> Option<ResetMode> {
+        ResetMode::ALL.into_iter().find(|mode| mode.key() == c)
+    }
+
+    pub fn to_span(&self, width: usize) -> Span {
+        let mode = self
+            .mode
+            .map(|mode| format!(" ({mode})"))
+            .unwrap_or_default();
+        let question = format!("Reset {} to {}{mode}?", self.branch, self.target);
+        let text = match self.state {
+            ResetState::ChoosingMode => format!("Reset {} to {}?", self.branch, self.target),
+            ResetState::CountingCommits => question,
+            ResetState::Confirming { lost_commits: 1 } => {
+                format!("{question} 1 commit would be lost")
+            }
+            ResetState::Confirming { lost_commits } => {
+                format!("{question} {lost_commits} commits would be lost")
+            }
+        };
+        Span::new(text).color_range(2, ..).fit(width)
+    }
+}
@@ -0,0 +1,76 @@
+use std::{collections::BTreeMap, path::Path};
+
+#[cfg(test)]
+use std::{cell::RefCell, path::PathBuf};
+
+use zellij_tile::prelude::*;
+
+use crate::command::CommandContext;
+
+pub trait GitRunner {
+    fn run(&self, cwd: Option<&Path>, args: &[&str], context: CommandContext);
+}
+
+#[derive(Default)]
+pub struct ZellijGitRunner;
+
+impl GitRunner for ZellijGitRunner {
+    fn run(&self, cwd: Option<&Path>, args: &[&str], context: CommandContext) {
+        let mut cmd = vec!["git"];
+        cmd.extend_from_slice(args);
+        let context = BTreeMap::from(context);
+        match cwd {
+            Some(cwd) => run_command_with_env_variables_and_cwd(
+                &cmd,
+                BTreeMap::new(),
+                cwd.to_owned(),
+                context,
+            ),
+            None => run_command(&cmd, context),
+        }
+    }
+}
+
+#[cfg(test)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Invocation {
+    pub cwd: Option<PathBuf>,
+    pub args: Vec<String>,
+    pub context: CommandContext,
+}
+
+#[cfg(test)]
+#[derive(Default)]
+pub struct RecordingGitRunner {
+    invocations: RefCell<Vec<Invocation>>,
+}
+
+#[cfg(test)]
+impl RecordingGitRunner {
+    pub fn pop_invocation(&self) -> Option<Invocation> {
+        let mut invocations = self.invocations.borrow_mut();
+        (!invocations.is_empty()).then(|| invocations.remove(0))
+    }
+
+    pub fn take_invocations(&self) -> Vec<Invocation> {
+        self.invocations.take()
+    }
+
+    pub fn take_args(&self) -> Vec<Vec<String>> {
+        self.take_invocations()
+            .into_iter()
+            .map(|invocation| invocation.args)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+impl GitRunner for RecordingGitRunner {
+    fn run(&self, cwd: Option<&Path>, args: &[&str], context: CommandContext) {
+        self.invocations.borrow_mut().push(Invocation {
+            cwd: cwd.map(Path::to_path_buf),
+            args: args.iter().map(|arg| String::from(*arg)).collect(),
+            context,
+        });
+    }
+}
@@ -1,16 +1,19 @@
-use std::{
-    collections::{BTreeMap, HashMap},
-    path::Path,
-};
+use std::{cmp::Reverse, collections::BTreeSet};
 
-use crate::branch::{Branch, LocalBranch, RemoteBranch, RemoteBranchRef, UpstreamInfo};
-use nucleo_matcher::{
-    Matcher,
-    pattern::{CaseMatching, Normalization, Pattern},
+use crate::{
+    branch::{Branch, LocalBranch, RemoteBranch, RemoteBranchRef, SearchField, UpstreamInfo},
+    canvas::{Canvas, Rect, Span},
+    commit::Commit,
+    filter::{self, Query},
+    input::{InputChange, LineInput},
+    remote::Remote,
+    table::{self, TableState},
 };
-use zellij_mason::{Rect, table::TableState};
+use nucleo_matcher::Matcher;
 use zellij_tile::prelude::*;
 
+pub type HelpEntry = (&'static str, &'static str);
+
 #[derive(Clone)]
 pub struct BranchesView<T> {
     pub branches: Vec<T>,
@@ -26,15 +29,6 @@ impl<T> Default for BranchesView<T> {
     }
 }
 
-impl<T> BranchesView<T> {
-    fn new(branches: Vec<T>) -> Self {
-        Self {
-            branches,
-            ..Self::default()
-        }
-    }
-}
-
 impl<T> BranchesView<T> {
     pub fn selected_branch(&self) -> Option<&T> {
         self.table_state
@@ -43,145 +37,399 @@ impl<T> BranchesView<T> {
     }
 }
 
+#[derive(Clone)]
+pub struct FilterEntry {
+    /// Index of the branch in `Tab::view`
+    pub index: usize,
+    /// Indices of the characters in the name of the branch that matched the filter
+    pub match_indices: Vec<usize>,
+}
+
+#[derive(Clone, Default)]
+pub struct FilteredView {
+    /// The input the entries were filtered with
+    pub query: String,
+    pub entries: Vec<FilterEntry>,
+    pub table_state: TableState,
+}
+
 #[derive(Clone)]
 pub struct Tab<T> {
     pub inited: bool,
-    pub input: String,
+    pub input: LineInput,
+    pub scope: Option<String>,
+    pub search_fields: Vec<SearchField>,
     pub view: BranchesView<T>,
-    pub filtered_view: Option<BranchesView<T>>,
+    pub filtered_view: Option<FilteredView>,
+    /// Widths of the columns of the branches passing the filter, computed when they are drawn
+    column_widths: Option<Vec<usize>>,
+    matcher: Matcher,
 }
 
 impl<T> Default for Tab<T> {
     fn default() -> Self {
         Self {
             inited: bool::default(),
-            input: String::default(),
+            input: LineInput::default(),
+            scope: Option::default(),
+            search_fields: vec![SearchField::Name],
             view: BranchesView::default(),
             filtered_view: Option::default(),
+            column_widths: Option::default(),
+            matcher: Matcher::default(),
         }
     }
 }
 
 impl<T> Tab<T> {
     pub fn select_down(&mut self) {
-        self.mut_current_view().table_state.offset_selected_index(1);
+        let row_count = self.visible_len();
+        self.table_state_mut().offset_selected_index(1, row_count);
     }
 
     pub fn select_up(&mut self) {
-        self.mut_current_view()
-            .table_state
-            .offset_selected_index(-1);
+        let row_count = self.visible_len();
+        self.table_state_mut().offset_selected_index(-1, row_count);
+    }
+
+    pub fn select_first(&mut self) {
+        self.table_state_mut().select_index(0);
+    }
+
+    pub fn selected_branch(&self) -> Option<&T> {
+        match &self.filtered_view {
+            Some(filtered_view) => filtered_view
+                .table_state
+                .selected_index()
+                .and_then(|selected_index| filtered_view.entries.get(selected_index))
+                .map(|entry| &self.view.branches[entry.index]),
+            None => self.view.selected_branch(),
+        }
     }
 
-    pub fn current_view(&self) -> &BranchesView<T> {
+    /// The number of branches passing the filter
+    pub fn visible_len(&self) -> usize {
         match &self.filtered_view {
-            Some(filtered_view) => filtered_view,
-            None => &self.view,
+            Some(filtered_view) => filtered_view.entries.len(),
+            None => self.view.branches.len(),
         }
     }
 
-    fn mut_current_view(&mut self) -> &mut BranchesView<T> {
+    /// The branch at `index` among the ones passing the filter, with the indices of its matched
+    /// characters
+    pub fn visible_branch(&self, index: usize) -> Option<(&T, &[usize])> {
+        match &self.filtered_view {
+            Some(filtered_view) => filtered_view.entries.get(index).map(|entry| {
+                (
+                    &self.view.branches[entry.index],
+                    entry.match_indices.as_slice(),
+                )
+            }),
+            None => self
+                .view
+                .branches
+                .get(index)
+                .map(|branch| (branch, [].as_slice())),
+        }
+    }
+
+    /// The branches passing the filter with the indices of their matched characters
+    pub fn visible_branches(&self) -> impl Iterator<Item = (&T, &[usize])> {
+        (0..self.visible_len()).filter_map(|index| self.visible_branch(index))
+    }
+
+    /// Draws the branches passing the filter, building the cells of only the rows that fit in
+    /// `rect`. Every row is measured with `widths` once after the branches or the filter change,
+    /// so that the columns keep their widths while scrolling. `widths` has to return the widths of
+    /// the cells `row` builds, without building them.
+    fn draw_table<const N: usize>(
+        &mut self,
+        canvas: &mut impl Canvas,
+        headers: [&str; N],
+        rect: Rect,
+        widths: impl Fn(&Self, &T) -> [usize; N],
+        row: impl Fn(&Self, &T, &[usize]) -> [Span; N],
+    ) {
+        let column_widths = match self
+            .column_widths
+            .as_deref()
+            .and_then(|column_widths| <[usize; N]>::try_from(column_widths).ok())
+        {
+            Some(column_widths) => column_widths,
+            None => {
+                let column_widths = table::column_widths(
+                    headers,
+                    self.visible_branches()
+                        .map(|(branch, _)| widths(self, branch)),
+                );
+                self.column_widths = Some(column_widths.to_vec());
+                column_widths
+            }
+        };
+        let row_count = self.visible_len();
+        let visible_range = self.table_state_mut().visible_range(row_count, rect);
+        let rows = visible_range
+            .filter_map(|index| self.visible_branch(index))
+            .map(|(branch, match_indices)| row(self, branch, match_indices))
+            .collect::<Vec<_>>();
+        table::draw(
+            canvas,
+            headers,
+            &rows,
+            column_widths,
+            rect,
+            self.table_state(),
+        );
+    }
+
+    fn table_state(&self) -> &TableState {
+        match &self.filtered_view {
+            Some(filtered_view) => &filtered_view.table_state,
+            None => &self.view.table_state,
+        }
+    }
+
+    fn table_state_mut(&mut self) -> &mut TableState {
         match &mut self.filtered_view {
-            Some(filtered_view) => filtered_view,
-            None => &mut self.view,
+            Some(filtered_view) => &mut filtered_view.table_state,
+            None => &mut self.view.table_state,
         }
     }
 }
 
-impl<T: Branch + Clone> Tab<T> {
-    pub fn push_to_input(&mut self, c: char) {
-        self.input.push(c);
-        self.update_filtered_view();
+impl<T: Branch> Tab<T> {
+    pub fn selected_branch_name(&self) -> Option<String> {
+        self.selected_branch()
+            .map(|branch| String::from(branch.name()))
     }
 
-    pub fn pop_from_input(&mut self) {
-        self.input.pop();
-        if self.input.is_empty() {
-            self.filtered_view = None;
-        } else {
-            self.update_filtered_view();
+    pub fn select_branch_by_name(&mut self, name: &str) {
+        let index = self
+            .visible_branches()
+            .position(|(branch, _)| branch.name() == name);
+        if let Some(index) = index {
+            self.table_state_mut().select_index(index);
         }
     }
 
-    pub fn update_filtered_view(&mut self) {
-        let branch_name_map: HashMap<&str, &T> = HashMap::from_iter(
-            self.view
-                .branches
-                .iter()
-                .map(|branch| (branch.name(), branch)),
-        );
-        let mut matcher = Matcher::new(nucleo_matcher::Config::DEFAULT);
-        let visible_branches = Pattern::parse(
-            self.input.as_str(),
-            CaseMatching::Smart,
-            Normalization::Smart,
-        )
-        .match_list(
-            self.view.branches.iter().map(|branch| branch.name()),
-            &mut matcher,
-        )
-        .iter()
-        .map(|(branch_name, _)| branch_name_map[branch_name])
-        .cloned()
-        .collect();
+    /// Replaces the branches while keeping the selected branch selected. When the selected branch is
+    /// gone, the branch that took its place in the list is selected instead.
+    pub fn set_branches(&mut self, branches: Vec<T>) {
+        let selected_index = self.table_state().selected_index();
+        self.keep_selection(selected_index, |tab| {
+            tab.view.branches = branches;
+            tab.refresh_filtered_view();
+        });
+    }
 
-        match &mut self.filtered_view {
-            Some(filtered_view) => {
-                filtered_view.branches = visible_branches;
+    // Runs `update` then selects the previously selected branch again, or the branch at
+    // `fallback_index` when it's not visible anymore
+    fn keep_selection(&mut self, fallback_index: Option<usize>, update: impl FnOnce(&mut Self)) {
+        let selected_branch_name = self.selected_branch_name();
+        update(self);
+        let index = selected_branch_name
+            .and_then(|name| {
+                self.visible_branches()
+                    .position(|(branch, _)| branch.name() == name)
+            })
+            .or(fallback_index)
+            .map(|index| index.min(self.visible_len().saturating_sub(1)))
+            .unwrap_or(0);
+        self.table_state_mut().select_index(index);
+    }
+
+    pub fn update_branch(&mut self, name: &str, update: impl Fn(&mut T)) {
+        self.column_widths = None;
+        self.view
+            .branches
+            .iter_mut()
+            .filter(|branch| branch.name() == name)
+            .for_each(update);
+    }
+
+    pub fn handle_input_key(&mut self, key: &KeyWithModifier) -> bool {
+        match self.input.handle_key(key) {
+            Some(InputChange::Text) => {
+                self.keep_selection(Some(0), Self::narrow_filtered_view);
+                true
             }
-            filtered_view @ None => {
-                *filtered_view = Some(BranchesView::new(visible_branches));
+            Some(InputChange::Cursor) => true,
+            None => false,
+        }
+    }
+
+    /// Inserts pasted text into the filter at the cursor
+    pub fn paste(&mut self, text: &str) -> bool {
+        if !self.input.insert_str(text) {
+            return false;
+        }
+        self.keep_selection(Some(0), Self::narrow_filtered_view);
+        true
+    }
+
+    pub fn set_filter(&mut self, filter: &str) {
+        self.keep_selection(Some(0), |tab| {
+            tab.input = LineInput::from(filter);
+            tab.refresh_filtered_view();
+        });
+    }
+
+    pub fn set_scope(&mut self, scope: Option<String>) {
+        self.keep_selection(Some(0), |tab| {
+            tab.scope = scope;
+            tab.refresh_filtered_view();
+        });
+    }
+
+    /// Filters every branch again. Has to be called when the branches or the scope change.
+    pub fn refresh_filtered_view(&mut self) {
+        self.column_widths = None;
+        if self.input.is_empty() && self.scope.is_none() {
+            self.filtered_view = None;
+        } else {
+            let candidates = (0..self.view.branches.len())
+                .filter(|index| {
+                    self.scope
+                        .as_deref()
+                        .is_none_or(|scope| self.view.branches[*index].scope() == Some(scope))
+                })
+                .collect();
+            self.update_filtered_view(candidates);
+        }
+    }
+
+    // When the input only grew, the branches that didn't match before can't match now, so only the
+    // previous matches are filtered again. This keeps typing responsive with tens of thousands of
+    // branches. Nucleo's threaded matcher isn't an option as plugins run without threads.
+    fn narrow_filtered_view(&mut self) {
+        match &self.filtered_view {
+            Some(filtered_view) if filter::narrows(&filtered_view.query, self.input.as_str()) => {
+                let candidates = filtered_view
+                    .entries
+                    .iter()
+                    .map(|entry| entry.index)
+                    .collect();
+                self.update_filtered_view(candidates);
             }
+            _ => self.refresh_filtered_view(),
         }
     }
+
+    fn update_filtered_view(&mut self, candidates: Vec<usize>) {
+        self.column_widths = None;
+        let query = Query::parse(self.input.as_str());
+        let entries = if query.is_empty() {
+            candidates
+                .into_iter()
+                .map(|index| FilterEntry {
+                    index,
+                    match_indices: Vec::new(),
+                })
+                .collect()
+        } else {
+            let mut buf = Vec::new();
+            let mut matches = candidates
+                .into_iter()
+                .filter_map(|index| {
+                    let branch_match = query.match_branch(
+                        &self.view.branches[index],
+                        &self.search_fields,
+                        &mut self.matcher,
+                        &mut buf,
+                    )?;
+                    Some((index, branch_match))
+                })
+                .collect::<Vec<_>>();
+            matches.sort_by_key(|(index, branch_match)| {
+                (
+                    Reverse(branch_match.name_matched),
+                    Reverse(branch_match.score),
+                    *index,
+                )
+            });
+            matches
+                .into_iter()
+                .map(|(index, branch_match)| FilterEntry {
+                    index,
+                    match_indices: branch_match.name_indices,
+                })
+                .collect()
+        };
+
+        let filtered_view = self.filtered_view.get_or_insert_default();
+        filtered_view.query = String::from(self.input.as_str());
+        filtered_view.entries = entries;
+    }
 }
 
 impl Tab<LocalBranch> {
-    pub fn create_branch(&mut self, cwd: Option<&impl AsRef<Path>>) {
-        match cwd {
-            Some(cwd) => run_command_with_env_variables_and_cwd(
-                &["git", "checkout", "-b", &self.input],
-                BTreeMap::new(),
-                cwd.as_ref().to_owned(),
-                BTreeMap::from([(String::from("command"), String::from("create"))]),
-            ),
-            None => run_command(
-                &["git", "checkout", "-b", &self.input],
-                BTreeMap::from([(String::from("command"), String::from("create"))]),
-            ),
-        }
-    }
+    pub const HELP: &'static [HelpEntry] = &[
+        ("<Ctrl-r>", "Refresh"),
+        ("<Ctrl-c>", "Create"),
+        ("<Ctrl-d>", "Delete"),
+        ("<Ctrl-x>", "Force delete"),
+        ("<Ctrl-z>", "Undo delete"),
+        ("<Ctrl-l>", "Open log"),
+        ("<Ctrl-p>", "Previous branch"),
+        ("<Ctrl-f>", "Fetch"),
+        ("<Ctrl-t>", "Set upstream"),
+        ("<Alt-t>", "Unset upstream"),
+        ("<Ctrl-e>", "Reset to selected"),
+        ("<Alt-e>", "Reset to upstream"),
+        ("<Alt-c>", "Cherry-pick"),
+        ("<Alt-n>", "Branch from HEAD"),
+        ("<Alt-m>", "Messages"),
+    ];
 
-    pub fn render_help(&self, rows: usize) {
-        let x = 0;
-        let y = rows - 2;
-
-        let (x, y) = print_command_help("<Ctrl-r>", "Refresh", x, y);
-        let (x, y) = print_help_separator(x, y);
-        let (x, y) = print_command_help("<Ctrl-c>", "Create", x, y);
-        let (x, y) = print_help_separator(x, y);
-        let (x, y) = print_command_help("<Ctrl-d>", "Delete", x, y);
-        let (x, y) = print_help_separator(x, y);
-        let (x, y) = print_command_help("<Ctrl-x>", "Force delete", x, y);
-        let (x, y) = print_help_separator(x, y);
-        let (x, y) = print_command_help("<Ctrl-l>", "Open log", x, y);
-        let (x, y) = print_help_separator(x, y);
-        let (x, y) = print_command_help("<Ctrl-p>", "Previous branch", x, y);
-        let (x, y) = print_help_separator(x, y);
-        print_command_help("<Ctrl-f>", "Fetch", x, y);
-    }
-
-    pub fn render_branch_list(&mut self, rect: Rect) {
-        let current_view = self.mut_current_view();
-        let table_rows = current_view
-            .branches
+    /// The help while a cherry-pick is stopped on a conflict, whose prompt `Alt-c` opens again
+    pub fn cherry_pick_help() -> Vec<HelpEntry> {
+        Self::HELP
             .iter()
-            .map(|branch| {
-                let name = Text::new(branch.name.clone());
-                let name = if branch.current {
-                    name.color_range(2, ..)
+            .map(|&(key, help_text)| match key {
+                "<Alt-c>" => (key, "Resolve cherry-pick"),
+                _ => (key, help_text),
+            })
+            .collect()
+    }
+
+    pub fn render_branch_list(&mut self, canvas: &mut impl Canvas, rect: Rect) {
+        self.draw_table(
+            canvas,
+            ["Name", "Upstream", "Sha", "Message"],
+            rect,
+            |_, branch| {
+                let name_width = branch.name.chars().count();
+                let upstream_width = match &branch.upstream_info {
+                    Some(UpstreamInfo { name, relationship }) => {
+                        name.chars().count()
+                            + relationship
+                                .as_ref()
+                                .map_or(0, |relationship| relationship.chars().count() + 2)
+                    }
+                    None => 1,
+                };
+                [
+                    if branch.detached {
+                        name_width + 2
+                    } else {
+                        name_width
+                    },
+                    upstream_width,
+                    branch.commit_sha.chars().count(),
+                    branch.commit_message.chars().count(),
+                ]
+            },
+            |_, branch, match_indices| {
+                let name = if branch.detached {
+                    Span::new(format!("({})", branch.name))
+                        .color_range(1, ..)
+                        .color_indices(3, match_indices.iter().map(|index| index + 1).collect())
+                } else if branch.current {
+                    Span::new(branch.name.clone())
+                        .color_range(2, ..)
+                        .color_indices(3, match_indices.to_vec())
                 } else {
-                    name
+                    Span::new(branch.name.clone()).color_indices(3, match_indices.to_vec())
                 };
                 let upstream_text = match &branch.upstream_info {
                     Some(UpstreamInfo {
@@ -196,91 +444,335 @@ impl Tab<LocalBranch> {
                 };
                 [
                     name,
-                    Text::new(upstream_text).color_range(1, ..),
-                    Text::new(branch.commit_sha.clone()),
-                    Text::new(branch.commit_message.clone()),
+                    Span::new(upstream_text).color_range(1, ..),
+                    Span::new(branch.commit_sha.clone()),
+                    Span::new(branch.commit_message.clone()),
                 ]
-            })
-            .collect::<Vec<_>>();
-        zellij_mason::table::draw(
-            ["Name", "Upstream", "Sha", "Message"],
-            &table_rows,
-            rect,
-            &mut current_view.table_state,
+            },
         );
     }
 }
 
 impl Tab<RemoteBranch> {
-    pub fn render_help(&self, rows: usize) {
-        let x = 0;
-        let y = rows - 2;
-
-        let (x, y) = print_command_help("<Ctrl-r>", "Refresh", x, y);
-        let (x, y) = print_help_separator(x, y);
-        print_command_help("<Ctrl-l>", "Open log", x, y);
+    pub fn cycle_scope(&mut self) {
+        let mut remotes: Vec<&str> = Vec::new();
+        for branch in &self.view.branches {
+            if !remotes.contains(&branch.remote()) {
+                remotes.push(branch.remote());
+            }
+        }
+        let next_scope = match &self.scope {
+            Some(scope) => remotes
+                .iter()
+                .position(|remote| remote == scope)
+                .and_then(|index| remotes.get(index + 1)),
+            None => remotes.first(),
+        }
+        .map(|remote| String::from(*remote));
+        self.set_scope(next_scope);
     }
 
-    pub fn render_branch_list(&mut self, rect: Rect) {
-        let current_view = self.mut_current_view();
-        let table_rows = current_view
+    pub const HELP: &'static [HelpEntry] = &[
+        ("<Ctrl-r>", "Refresh"),
+        ("<Ctrl-f>", "Fetch all"),
+        ("<Ctrl-o>", "Filter remote"),
+        ("<Ctrl-l>", "Open log"),
+        ("<Alt-d>", "Update default branch"),
+        ("<Alt-m>", "Messages"),
+    ];
+
+    pub const PICKER_HELP: &'static [HelpEntry] =
+        &[("<Enter>", "Set upstream"), ("<Esc>", "Cancel")];
+
+    fn remote_branch_sha(&self, name: &str) -> Option<&str> {
+        self.view
             .branches
             .iter()
-            .map(|branch| {
-                let name = Text::new(branch.name.clone());
+            .find_map(|branch| match &branch.reference {
+                RemoteBranchRef::Commit { sha, .. } if branch.name == name => Some(sha.as_str()),
+                _ => None,
+            })
+    }
+
+    pub fn render_branch_list(&mut self, canvas: &mut impl Canvas, rect: Rect) {
+        self.draw_table(
+            canvas,
+            ["Name", "Sha", "Message"],
+            rect,
+            |tab, branch| {
+                let name_width = branch.name.chars().count();
                 match &branch.reference {
-                    RemoteBranchRef::Branch(ref_branch) => [
-                        name,
-                        Text::new(" "),
-                        Text::new(ref_branch.clone()),
-                        Text::new(" "),
+                    RemoteBranchRef::Branch(target) => [
+                        name_width,
+                        tab.remote_branch_sha(target)
+                            .map_or(1, |sha| sha.chars().count()),
+                        "default branch: ".len() + target.chars().count(),
                     ],
-                    RemoteBranchRef::Commit { sha, message } => [
+                    RemoteBranchRef::Commit { sha, message } => {
+                        [name_width, sha.chars().count(), message.chars().count()]
+                    }
+                }
+            },
+            |tab, branch, match_indices| {
+                let name = Span::new(branch.name.clone())
+                    .color_range(0, ..branch.remote().len())
+                    .color_indices(3, match_indices.to_vec());
+                match &branch.reference {
+                    RemoteBranchRef::Branch(target) => [
                         name,
-                        Text::new(sha.clone()),
-                        Text::new(" "),
-                        Text::new(message.clone()),
+                        Span::new(tab.remote_branch_sha(target).unwrap_or(" ")),
+                        Span::new(format!("default branch: {target}")).color_range(1, ..),
                     ],
+                    RemoteBranchRef::Commit { sha, message } => {
+                        [name, Span::new(sha.clone()), Span::new(message.clone())]
+                    }
                 }
+            },
+        );
+    }
+}
+
+impl Tab<Commit> {
+    pub const PICKER_HELP: &'static [HelpEntry] = &[
+        ("<Tab>", "Mark"),
+        ("<Enter>", "Cherry-pick"),
+        ("<Esc>", "Cancel"),
+    ];
+
+    pub const CONFLICT_HELP: &'static [HelpEntry] = &[
+        ("c", "Continue"),
+        ("s", "Skip commit"),
+        ("a", "Abort"),
+        ("<Esc>", "Hide"),
+    ];
+
+    /// Search fields of commit lists. Commits are named by their sha, which fuzzy matches too
+    /// many words to be matched first.
+    pub const SEARCH_FIELDS: [SearchField; 3] =
+        [SearchField::Message, SearchField::Author, SearchField::Sha];
+
+    pub fn for_commits() -> Self {
+        Self {
+            search_fields: Self::SEARCH_FIELDS.to_vec(),
+            ..Self::default()
+        }
+    }
+
+    /// Renders the commits, marking the ones whose sha is in `marked`
+    pub fn render_commit_list(
+        &mut self,
+        canvas: &mut impl Canvas,
+        rect: Rect,
+        marked: &BTreeSet<String>,
+    ) {
+        self.draw_table(
+            canvas,
+            [" ", "Sha", "Author", "Message"],
+            rect,
+            |_, commit| {
+                let [sha, author, subject] = commit_widths(commit);
+                [1, sha, author, subject]
+            },
+            |_, commit, _| {
+                let mark = if marked.contains(&commit.sha) {
+                    Span::new("+").color_range(2, ..)
+                } else {
+                    Span::new(" ")
+                };
+                let [sha, author, subject] = commit_columns(commit);
+                [mark, sha, author, subject]
+            },
+        );
+    }
+}
+
+/// The widths of the cells `commit_columns` builds
+fn commit_widths(commit: &Commit) -> [usize; 3] {
+    [
+        commit.sha.chars().count(),
+        commit.author.chars().count(),
+        commit.subject.chars().count(),
+    ]
+}
+
+fn commit_columns(commit: &Commit) -> [Span; 3] {
+    [
+        Span::new(commit.sha.clone()),
+        Span::new(commit.author.clone()).color_range(1, ..),
+        Span::new(commit.subject.clone()),
+    ]
+}
+
+/// Commits of all refs, newest first. They are loaded a page at a time, whenever the selection
+/// gets close to the end of the loaded commits.
+pub struct CommitsTab {
+    pub commits: Tab<Commit>,
+    pub loading: bool,
+    pub exhausted: bool,
+}
+
+impl Default for CommitsTab {
+    fn default() -> Self {
+        Self {
+            commits: Tab::for_commits(),
+            loading: false,
+            exhausted: false,
+        }
+    }
+}
+
+impl CommitsTab {
+    pub const PAGE_SIZE: usize = 100;
+    const LOAD_MARGIN: usize = 10;
+
+    pub const HELP: &'static [HelpEntry] = &[
+        ("<Enter>", "Checkout"),
+        ("<Ctrl-r>", "Refresh"),
+        ("<Ctrl-c>", "Create branch"),
+        ("<Ctrl-t>", "Tag"),
+        ("<Alt-c>", "Cherry-pick"),
+        ("<Ctrl-l>", "Show"),
+        ("<Alt-m>", "Messages"),
+    ];
+
+    /// Whether the next page should be loaded for the selection to stay clear of the end of the
+    /// loaded commits. The selection's position among all loaded commits is used even when
+    /// filtered, so that typing a filter doesn't load a page on every key.
+    pub fn needs_more(&self) -> bool {
+        let position = match &self.commits.filtered_view {
+            Some(filtered_view) => filtered_view
+                .table_state
+                .selected_index()
+                .and_then(|index| filtered_view.entries.get(index))
+                .map(|entry| entry.index),
+            None => Some(self.commits.view.table_state.selected_index().unwrap_or(0)),
+        };
+        !self.loading
+            && !self.exhausted
+            && position.is_some_and(|position| {
+                position + Self::LOAD_MARGIN >= self.commits.view.branches.len()
             })
-            .collect::<Vec<_>>();
-        zellij_mason::table::draw(
-            ["Name", "Sha", "Ref", "Message"],
-            &table_rows,
+    }
+
+    /// Adds a page of commits loaded after skipping `skip` commits. The first page replaces the
+    /// loaded commits.
+    pub fn add_page(&mut self, skip: usize, page_size: usize, commits: Vec<Commit>) {
+        self.loading = false;
+        self.exhausted = commits.len() < page_size;
+        let mut all_commits = if skip == 0 {
+            Vec::new()
+        } else {
+            self.commits.view.branches[..skip.min(self.commits.view.branches.len())].to_vec()
+        };
+        all_commits.extend(commits);
+        self.commits.set_branches(all_commits);
+    }
+
+    pub fn render_commit_list(&mut self, canvas: &mut impl Canvas, rect: Rect) {
+        self.commits.draw_table(
+            canvas,
+            ["Sha", "Author", "Message"],
             rect,
-            &mut current_view.table_state,
+            |_, commit| commit_widths(commit),
+            |_, commit, _| commit_columns(commit),
         );
     }
 }
 
-fn print_command_help(
-    key: impl AsRef<str> + ToString,
-    help_text: impl AsRef<str> + ToString,
-    mut x: usize,
-    y: usize,
-) -> (usize, usize) {
-    print_text_with_coordinates(
-        Text::new(key.as_ref()).color_range(3, 0..),
-        x,
-        y,
-        None,
-        None,
-    );
-
-    x += key.as_ref().chars().count();
-    let separator = " - ";
-    print_text_with_coordinates(Text::new(separator), x, y, None, None);
-
-    x += separator.chars().count();
-
-    print_text_with_coordinates(Text::new(help_text.as_ref()), x, y, None, None);
-    x += help_text.as_ref().chars().count();
-    (x, y)
+#[derive(Default)]
+pub struct RemotesTab {
+    pub inited: bool,
+    pub input: LineInput,
+    pub remotes: Vec<Remote>,
+    pub table_state: TableState,
+    /// The remote waiting for the user to confirm its removal
+    pub removal: Option<String>,
+}
+
+impl RemotesTab {
+    pub fn select_down(&mut self) {
+        self.table_state
+            .offset_selected_index(1, self.remotes.len());
+    }
+
+    pub fn select_up(&mut self) {
+        self.table_state
+            .offset_selected_index(-1, self.remotes.len());
+    }
+
+    pub fn selected_remote(&self) -> Option<&Remote> {
+        self.table_state
+            .selected_index()
+            .and_then(|selected_index| self.remotes.get(selected_index))
+    }
+
+    pub const HELP: &'static [HelpEntry] = &[
+        ("<Ctrl-r>", "Refresh"),
+        ("<Ctrl-a>", "Add <name> <url>"),
+        ("<Ctrl-e>", "Rename"),
+        ("<Ctrl-s>", "Set URL"),
+        ("<Ctrl-d>", "Remove"),
+        ("<Alt-m>", "Messages"),
+    ];
+    pub const REMOVAL_HELP: &'static [HelpEntry] = &[("y", "Remove"), ("<Esc>", "Cancel")];
+
+    pub fn render_remote_list(&mut self, canvas: &mut impl Canvas, rect: Rect) {
+        let headers = ["Name", "Fetch URL", "Push URL"];
+        let row = |remote: &Remote| {
+            [
+                Span::new(remote.name.clone()),
+                Span::new(remote.fetch_url.clone()),
+                Span::new(remote.push_url.clone().unwrap_or_else(|| String::from(" "))),
+            ]
+        };
+        let column_widths = table::column_widths(
+            headers,
+            self.remotes.iter().map(|remote| {
+                [
+                    remote.name.chars().count(),
+                    remote.fetch_url.chars().count(),
+                    remote
+                        .push_url
+                        .as_ref()
+                        .map_or(1, |push_url| push_url.chars().count()),
+                ]
+            }),
+        );
+        let visible_range = self.table_state.visible_range(self.remotes.len(), rect);
+        let table_rows = self.remotes[visible_range]
+            .iter()
+            .map(row)
+            .collect::<Vec<_>>();
+        table::draw(
+            canvas,
+            headers,
+            &table_rows,
+            column_widths,
+            rect,
+            &self.table_state,
+        );
+    }
 }
 
-fn print_help_separator(x: usize, y: usize) -> (usize, usize) {
-    let text = ", ";
-    print_text_with_coordinates(Text::new(text), x, y, None, None);
+const HELP_SEPARATOR: &str = ", ";
 
-    (x + text.chars().count(), y)
+pub fn help_lines(help: &[HelpEntry], width: usize) -> Vec<Span> {
+    let mut lines = Vec::new();
+    for (key, help_text) in help {
+        let entry = format!("{key} - {help_text}");
+        let mut line: Span = lines.pop().unwrap_or_default();
+        if !line.content.is_empty() {
+            if line.width() + HELP_SEPARATOR.len() + entry.chars().count() > width {
+                lines.push(line);
+                line = Span::default();
+            } else {
+                line.content.push_str(HELP_SEPARATOR);
+            }
+        }
+        let key_start = line.width();
+        line = line.color_range(3, key_start..key_start + key.chars().count());
+        line.content.push_str(&entry);
+        lines.push(line);
+    }
+    lines
 }
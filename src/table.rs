@@ -0,0 +1,133 @@
+use std::ops::Range;
+
+use crate::canvas::{Canvas, Rect, Span};
+
+const COLUMN_GAP: usize = 2;
+
+#[derive(Debug, Clone, Default)]
+pub struct TableState {
+    selected_index: Option<usize>,
+    offset: usize,
+}
+
+impl TableState {
+    pub fn select_index(&mut self, index: usize) {
+        self.selected_index = Some(index);
+    }
+
+    pub fn selected_index(&self) -> Option<usize> {
+        self.selected_index
+    }
+
+    /// Moves the selection by `offset`, staying within the `row_count` rows of the table
+    pub fn offset_selected_index(&mut self, offset: isize, row_count: usize) {
+        self.selected_index = match self.selected_index {
+            _ if row_count == 0 => None,
+            Some(index) => Some(index.saturating_add_signed(offset).min(row_count - 1)),
+            None => Some(0),
+        };
+    }
+
+    /// Keeps the selection within `row_count` rows and scrolls it into view. Returns the range of
+    /// the rows that fit in `rect` below the header.
+    pub fn visible_range(&mut self, row_count: usize, rect: Rect) -> Range<usize> {
+        let visible_rows = rect.height.saturating_sub(1);
+        self.clamp(row_count, visible_rows);
+        self.offset..(self.offset + visible_rows).min(row_count)
+    }
+
+    fn clamp(&mut self, row_count: usize, visible_rows: usize) {
+        self.selected_index = match self.selected_index {
+            _ if row_count == 0 => None,
+            Some(index) => Some(index.min(row_count - 1)),
+            None => None,
+        };
+        if let Some(index) = self.selected_index {
+            if index < self.offset {
+                self.offset = index;
+            } else if visible_rows > 0 && index >= self.offset + visible_rows {
+                self.offset = index + 1 - visible_rows;
+            }
+        }
+        self.offset = self
+            .offset
+            .min(row_count.saturating_sub(visible_rows.max(1)));
+    }
+}
+
+/// The width of the widest cell of each column, headers included, from the widths of the cells of
+/// each row
+pub fn column_widths<const N: usize>(
+    headers: [&str; N],
+    rows: impl IntoIterator<Item = [usize; N]>,
+) -> [usize; N] {
+    let mut column_widths: [usize; N] = headers.map(|header| header.chars().count());
+    for row in rows {
+        for (width, cell_width) in column_widths.iter_mut().zip(row) {
+            *width = (*width).max(cell_width);
+        }
+    }
+    column_widths
+}
+
+/// Draws the `rows` in the range returned by [`TableState::visible_range`]. The columns are laid
+/// out by the `column_widths` of every row, so that they don't move while scrolling.
+pub fn draw<const N: usize>(
+    canvas: &mut impl Canvas,
+    headers: [&str; N],
+    rows: &[[Span; N]],
+    mut column_widths: [usize; N],
+    rect: Rect,
+    state: &TableState,
+) {
+    if rect.width == 0 || rect.height == 0 {
+        return;
+    }
+
+    // Columns are narrowed from the last one towards the first, but not below their header, so
+    // that the leading columns like the name stay readable for as long as possible
+    let total_width = column_widths.iter().sum::<usize>() + COLUMN_GAP * N.saturating_sub(1);
+    let mut excess = total_width.saturating_sub(rect.width);
+    for (width, header) in column_widths.iter_mut().zip(headers).rev() {
+        let shrink = excess.min(width.saturating_sub(header.chars().count()));
+        *width -= shrink;
+        excess -= shrink;
+    }
+
+    let mut column_xs = [0; N];
+    let mut x = 0;
+    for (column_x, width) in column_xs.iter_mut().zip(&mut column_widths) {
+        *column_x = x;
+        *width = (*width).min(rect.width.saturating_sub(x));
+        x += *width + COLUMN_GAP;
+    }
+
+    let draw_row = |canvas: &mut _, cells: [Span; N], y: usize| {
+        for ((cell, column_x), width) in cells.into_iter().zip(column_xs).zip(column_widths) {
+            if width == 0 {
+                continue;
+            }
+            let gap = COLUMN_GAP.min(rect.width - column_x - width);
+            Canvas::print_text(
+                canvas,
+                &cell.fit(width).fit(width + gap),
+                rect.x + column_x,
+                y,
+            );
+        }
+    };
+
+    draw_row(
+        canvas,
+        headers.map(|header| Span::new(header).color_range(0, ..)),
+        rect.y,
+    );
+    for (visible_index, row) in rows.iter().enumerate() {
+        let cells = if state.selected_index == Some(state.offset + visible_index) {
+            row.clone().map(Span::selected)
+        } else {
+            row.clone()
+        };
+        draw_row(canvas, cells, rect.y + 1 + visible_index);
+    }
+}
@@ -0,0 +1,76 @@
+use zellij_tile::prelude::*;
+
+use super::{
+    LOCAL_BRANCHES, TestGit, branch_names, complete, ctrl, loaded_git, match_indices, type_text,
+};
+use crate::{branch::SearchField, filter};
+
+fn filter(git: &mut TestGit, query: &str) -> Vec<String> {
+    git.update(ctrl('u'));
+    type_text(git, query);
+    branch_names(git).into_iter().map(String::from).collect()
+}
+
+#[test]
+fn field_scoped_queries() {
+    let mut git = loaded_git();
+
+    assert_eq!(filter(&mut git, "msg:init"), ["main"]);
+    assert_eq!(filter(&mut git, "sha:333"), ["topic"]);
+    assert!(filter(&mut git, "sha:13").is_empty());
+    assert_eq!(filter(&mut git, "up:origin"), ["feature", "main"]);
+    assert_eq!(filter(&mut git, "up:origin msg:feat"), ["feature"]);
+    assert_eq!(filter(&mut git, "name:top"), ["topic"]);
+}
+
+#[test]
+fn unscoped_query_matches_name_by_default() {
+    let mut git = loaded_git();
+
+    assert!(filter(&mut git, "progress").is_empty());
+    assert_eq!(filter(&mut git, "msg:"), ["feature", "main", "topic"]);
+}
+
+#[test]
+fn name_matches_are_ranked_first() {
+    let mut git = loaded_git();
+    git.local_branches_tab.search_fields = SearchField::ALL.to_vec();
+    git.list_local_branches(false);
+    complete(
+        &mut git,
+        0,
+        &format!("{LOCAL_BRANCHES}  fix-login 4444444 Work on login\n  work 5555555 Fix typo\n"),
+        "",
+    );
+
+    assert_eq!(filter(&mut git, "work"), ["work", "topic", "fix-login"]);
+    assert_eq!(filter(&mut git, "progress"), ["topic"]);
+    assert_eq!(match_indices(&git), [Vec::<usize>::new()]);
+}
+
+#[test]
+fn appended_characters_narrow_the_query() {
+    assert!(filter::narrows("", "fe"));
+    assert!(filter::narrows("fe", "feat"));
+    assert!(filter::narrows("msg:", "msg:fix"));
+    assert!(filter::narrows("fe msg:", "fe msg:fix"));
+    assert!(!filter::narrows("feat", "fe"));
+    assert!(!filter::narrows("msg", "msg:"));
+    assert!(!filter::narrows("!fe", "!feat"));
+    assert!(!filter::narrows("fe$", "fe$a"));
+}
+
+#[test]
+fn widening_query_filters_every_branch_again() {
+    let mut git = loaded_git();
+
+    assert!(filter(&mut git, "sha").is_empty());
+    type_text(&mut git, ":");
+    assert_eq!(branch_names(&git), ["feature", "main", "topic"]);
+    type_text(&mut git, "2");
+    assert_eq!(branch_names(&git), ["main"]);
+
+    assert_eq!(filter(&mut git, "!ma"), ["feature", "topic"]);
+    type_text(&mut git, "x");
+    assert_eq!(branch_names(&git), ["feature", "main", "topic"]);
+}
@@ -0,0 +1,108 @@
+use zellij_tile::prelude::*;
+
+use crate::input::{InputChange, LineInput};
+
+fn input_at(text: &str, cursor_from_end: usize) -> LineInput {
+    let mut input = LineInput::from(text);
+    for _ in 0..cursor_from_end {
+        input.move_left();
+    }
+    input
+}
+
+fn rendered(input: &LineInput) -> String {
+    input.to_span("", 80).content.trim_end().to_string()
+}
+
+#[test]
+fn edits_at_cursor() {
+    let mut input = input_at("featre", 2);
+    input.insert('u');
+    assert_eq!(rendered(&input), "featu|re");
+
+    input.delete_forward();
+    assert_eq!(rendered(&input), "featu|e");
+
+    input.delete_backward();
+    input.move_home();
+    input.delete_backward();
+    assert_eq!(rendered(&input), "|feate");
+}
+
+#[test]
+fn handles_multibyte_characters() {
+    let mut input = LineInput::from("ágazat-ő");
+    input.move_left();
+    input.move_left();
+    assert_eq!(rendered(&input), "ágazat|-ő");
+
+    input.delete_backward();
+    input.insert('é');
+    assert_eq!(input.as_str(), "ágazaé-ő");
+
+    input.move_home();
+    input.move_right();
+    input.delete_backward();
+    assert_eq!(rendered(&input), "|gazaé-ő");
+}
+
+#[test]
+fn word_motions_stop_at_separators() {
+    let mut input = LineInput::from("feature/new-thing");
+    input.move_word_left();
+    assert_eq!(rendered(&input), "feature/new-|thing");
+    input.move_word_left();
+    input.move_word_left();
+    assert_eq!(rendered(&input), "|feature/new-thing");
+    input.move_word_left();
+    assert_eq!(rendered(&input), "|feature/new-thing");
+
+    input.move_word_right();
+    assert_eq!(rendered(&input), "feature|/new-thing");
+    input.move_word_right();
+    assert_eq!(rendered(&input), "feature/new|-thing");
+}
+
+#[test]
+fn editing_keys() {
+    let mut input = LineInput::from("feature/new-thing");
+
+    let ctrl_w = KeyWithModifier::new(BareKey::Char('w')).with_ctrl_modifier();
+    assert_eq!(input.handle_key(&ctrl_w), Some(InputChange::Text));
+    assert_eq!(input.as_str(), "feature/new-");
+    input.handle_key(&ctrl_w);
+    assert_eq!(input.as_str(), "feature/");
+
+    let alt_b = KeyWithModifier::new(BareKey::Char('b')).with_alt_modifier();
+    assert_eq!(input.handle_key(&alt_b), Some(InputChange::Cursor));
+    assert_eq!(input.handle_key(&alt_b), None);
+
+    let end = KeyWithModifier::new(BareKey::End);
+    assert_eq!(input.handle_key(&end), Some(InputChange::Cursor));
+    assert_eq!(rendered(&input), "feature/|");
+
+    let ctrl_x = KeyWithModifier::new(BareKey::Char('x')).with_ctrl_modifier();
+    assert_eq!(input.handle_key(&ctrl_x), None);
+
+    let ctrl_u = KeyWithModifier::new(BareKey::Char('u')).with_ctrl_modifier();
+    assert_eq!(input.handle_key(&ctrl_u), Some(InputChange::Text));
+    assert!(input.is_empty());
+}
+
+#[test]
+fn pasted_text_stays_on_one_line() {
+    let mut input = LineInput::from("fix/");
+    assert!(input.insert_str("login\r\npage\t"));
+    assert_eq!(rendered(&input), "fix/loginpage|");
+    assert!(!input.insert_str("\n"));
+}
+
+#[test]
+fn long_text_scrolls_to_keep_cursor_visible() {
+    let input = input_at("feature/very-long-branch-name", 5);
+
+    assert_eq!(
+        input.to_span("Branch: ", 30).content,
+        "Branch: ture/very-long-branch|"
+    );
+}
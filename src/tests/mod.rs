@@ -0,0 +1,1461 @@
+use std::{
+    collections::BTreeMap,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use zellij_tile::prelude::*;
+
+mod filter;
+mod input;
+mod render;
+
+use crate::{
+    BranchType, DeletedBranch, Git,
+    branch::{LocalBranch, RemoteBranch, RemoteBranchRef, UpstreamInfo},
+    command::{Command, CommandContext},
+    host::RecordingHost,
+    input::LineInput,
+    message::{Level, Message, Messages},
+    remote::Remote,
+    reset::ResetState,
+    runner::{Invocation, RecordingGitRunner},
+    tab::CommitsTab,
+};
+
+type TestGit = Git<RecordingGitRunner, RecordingHost>;
+
+const LOCAL_BRANCHES: &str = "\
+  feature 1111111 [origin/feature: ahead 1] Add feature
+* main    2222222 [origin/main] Initial commit
+  topic   3333333 Work in progress
+";
+
+const REMOTE_BRANCHES: &str = "\
+  origin/HEAD    -> origin/main
+  origin/feature 1111111 Add feature
+  origin/main    2222222 Initial commit
+";
+
+fn git() -> TestGit {
+    Git {
+        cwd: Some(PathBuf::from("/repo")),
+        ..Default::default()
+    }
+}
+
+fn loaded_git() -> TestGit {
+    let mut git = git();
+    git.local_branches_tab.inited = true;
+    git.list_local_branches(false);
+    complete(&mut git, 0, LOCAL_BRANCHES, "");
+    git
+}
+
+fn key(bare_key: BareKey) -> Event {
+    Event::Key(KeyWithModifier::new(bare_key))
+}
+
+fn ctrl(c: char) -> Event {
+    Event::Key(KeyWithModifier::new(BareKey::Char(c)).with_ctrl_modifier())
+}
+
+fn type_text(git: &mut TestGit, text: &str) {
+    for c in text.chars() {
+        git.update(key(BareKey::Char(c)));
+    }
+}
+
+/// Completes the oldest pending git command with the given result, the same way Zellij reports it.
+fn complete(git: &mut TestGit, exit_code: i32, stdout: &str, stderr: &str) -> bool {
+    let invocation = git
+        .runner
+        .pop_invocation()
+        .expect("no git command is pending");
+    respond(git, invocation, exit_code, stdout, stderr)
+}
+
+fn respond(
+    git: &mut TestGit,
+    invocation: Invocation,
+    exit_code: i32,
+    stdout: &str,
+    stderr: &str,
+) -> bool {
+    git.update(Event::RunCommandResult(
+        Some(exit_code),
+        stdout.as_bytes().to_vec(),
+        stderr.as_bytes().to_vec(),
+        BTreeMap::from(invocation.context),
+    ))
+}
+
+fn branch_names(git: &TestGit) -> Vec<&str> {
+    git.local_branches_tab
+        .visible_branches()
+        .map(|(branch, _)| branch.name.as_str())
+        .collect()
+}
+
+fn match_indices(git: &TestGit) -> Vec<Vec<usize>> {
+    git.local_branches_tab
+        .visible_branches()
+        .map(|(_, match_indices)| match_indices.to_vec())
+        .collect()
+}
+
+fn local_branch(name: &str, upstream: Option<&str>) -> LocalBranch {
+    LocalBranch {
+        name: String::from(name),
+        commit_sha: String::from("abc1234"),
+        upstream_info: upstream.map(|upstream| UpstreamInfo {
+            name: String::from(upstream),
+            relationship: None,
+        }),
+        ..Default::default()
+    }
+}
+
+fn args(args: &[&[&str]]) -> Vec<Vec<String>> {
+    args.iter()
+        .map(|args| args.iter().map(|arg| String::from(*arg)).collect())
+        .collect()
+}
+
+#[test]
+fn list_commands_run_in_cwd() {
+    let mut git = git();
+    git.list_local_branches(false);
+
+    assert_eq!(
+        git.runner.take_invocations(),
+        vec![Invocation {
+            cwd: Some(PathBuf::from("/repo")),
+            args: vec![String::from("branch"), String::from("-vv")],
+            context: CommandContext {
+                command: Command::ListLocalBranches {
+                    preserve_selection: false
+                },
+                generation: 1,
+            },
+        }]
+    );
+
+    git.list_remote_branches(true);
+    git.list_remotes();
+    assert_eq!(
+        git.runner.take_args(),
+        args(&[&["branch", "-r", "-v"], &["remote", "-v"]])
+    );
+}
+
+#[test]
+fn branch_actions_produce_git_arguments() {
+    let mut git = git();
+
+    git.switch_to_branch(&local_branch("feature", None));
+    git.switch_to_previous_branch();
+    git.create_branch("feature/new", None);
+    git.delete_branch("feature", false);
+    git.delete_branch("feature", true);
+    git.track_remote_branch(&RemoteBranch {
+        name: String::from("origin/feature"),
+        reference: RemoteBranchRef::Commit {
+            sha: String::from("abc1234"),
+            message: String::from("Add feature"),
+        },
+    });
+
+    assert_eq!(
+        git.runner.take_args(),
+        args(&[
+            &["switch", "feature"],
+            &["switch", "-"],
+            &["checkout", "-b", "feature/new"],
+            &["branch", "-d", "feature"],
+            &["branch", "-D", "feature"],
+            &["checkout", "--track", "origin/feature"],
+        ])
+    );
+}
+
+#[test]
+fn fetch_uses_upstream_refspec() {
+    let mut git = git();
+
+    git.fetch(&local_branch("feature", Some("origin/feature")))
+        .unwrap();
+    assert_eq!(
+        git.runner.take_args(),
+        args(&[&["fetch", "origin", "feature:feature"]])
+    );
+
+    assert!(git.fetch(&local_branch("feature", None)).is_err());
+    assert!(git.runner.take_invocations().is_empty());
+}
+
+#[test]
+fn fetch_all_runs_once_while_in_progress() {
+    let mut git = git();
+
+    git.fetch_all(false);
+    git.fetch_all(false);
+
+    assert_eq!(
+        git.runner.take_args(),
+        args(&[&["fetch", "--all", "--prune"]])
+    );
+}
+
+#[test]
+fn fetch_all_reports_only_when_not_in_background() {
+    let mut git = git();
+
+    git.fetch_all(true);
+    complete(&mut git, 0, "", "");
+    assert!(git.messages.latest().is_none());
+    git.runner.take_invocations();
+
+    cli_pipe(&mut git, "fetch", "");
+    complete(&mut git, 0, "", "");
+    assert_eq!(pipe_replies(&git), ["Fetched all remotes\n"]);
+    assert_eq!(
+        git.messages.latest().map(|message| message.title.as_str()),
+        Some("Fetched all remotes")
+    );
+}
+
+#[test]
+fn timer_fetches_all_remotes_periodically() {
+    let mut git = git();
+    git.fetch_interval = Some(Duration::from_secs(300));
+
+    git.schedule_fetch();
+    assert!(git.next_fetch.is_some());
+    assert_eq!(*git.host.timeouts.borrow(), [300.0]);
+    assert!(git.runner.take_invocations().is_empty());
+
+    git.next_fetch = git
+        .next_fetch
+        .map(|next_fetch| next_fetch - Duration::from_secs(301));
+    git.update(Event::Timer(300.0));
+    let invocations = git.runner.take_invocations();
+    assert_eq!(invocations.len(), 1);
+    assert_eq!(invocations[0].args, ["fetch", "--all", "--prune"]);
+    assert_eq!(
+        invocations[0].context.command,
+        Command::FetchAll { background: true }
+    );
+    assert!(git.next_fetch.is_none());
+}
+
+#[test]
+fn interrupted_fetch_can_run_again() {
+    let mut git = git();
+
+    git.fetch_all(false);
+    let invocation = git.runner.pop_invocation().unwrap();
+    git.update(Event::RunCommandResult(
+        None,
+        Vec::new(),
+        Vec::new(),
+        BTreeMap::from(invocation.context),
+    ));
+    git.fetch_all(false);
+
+    assert_eq!(
+        git.runner.take_args(),
+        args(&[&["fetch", "--all", "--prune"]])
+    );
+}
+
+#[test]
+fn fetch_age_is_updated_every_minute() {
+    let mut git = git();
+    git.fetch_all(true);
+    complete(&mut git, 0, "", "");
+    assert_eq!(git.minutes_since_fetch(), Some(0));
+    assert!(
+        git.host
+            .timeouts
+            .borrow()
+            .iter()
+            .any(|seconds| (59.0..=60.0).contains(seconds))
+    );
+
+    let a_minute_ago =
+        |instant: Option<Instant>| instant.map(|instant| instant - Duration::from_secs(61));
+    git.last_fetch = a_minute_ago(git.last_fetch);
+    git.fetch_age_tick = a_minute_ago(git.fetch_age_tick);
+    let timeouts = git.host.timeouts.borrow().len();
+    assert!(git.update(Event::Timer(60.0)));
+    assert_eq!(git.minutes_since_fetch(), Some(1));
+    assert_eq!(git.host.timeouts.borrow().len(), timeouts + 1);
+}
+
+#[test]
+fn pruned_branches_notice_is_dismissed_before_closing() {
+    let mut git = loaded_git();
+    git.fetch_all(false);
+    complete(
+        &mut git,
+        0,
+        "",
+        " - [deleted]         (none)     -> origin/feature\n",
+    );
+    assert_eq!(
+        git.notice.as_deref(),
+        Some("Pruned origin/feature. Local branches tracking them: feature")
+    );
+
+    git.runner.take_invocations();
+    git.fetch_all(true);
+    complete(&mut git, 0, "", "");
+    assert!(git.notice.is_some());
+
+    git.update(key(BareKey::Esc));
+    assert!(git.notice.is_none());
+    assert!(!git.host.closed.get());
+
+    git.update(key(BareKey::Esc));
+    assert!(git.host.closed.get());
+}
+
+#[test]
+fn upstream_actions_produce_git_arguments() {
+    let mut git = git();
+
+    git.set_upstream("feature", "origin/main");
+    git.unset_upstream(&local_branch("feature", Some("origin/main")))
+        .unwrap();
+    assert!(git.unset_upstream(&local_branch("feature", None)).is_err());
+
+    assert_eq!(
+        git.runner.take_args(),
+        args(&[
+            &["branch", "--set-upstream-to=origin/main", "feature"],
+            &["branch", "--unset-upstream", "feature"],
+        ])
+    );
+}
+
+#[test]
+fn remote_actions_produce_git_arguments() {
+    let mut git = git();
+    git.remotes_tab.input = LineInput::from("upstream https://example.com/repo.git");
+
+    git.add_remote().unwrap();
+    git.remotes_tab.input = LineInput::from("upstream");
+    assert!(git.add_remote().is_err());
+
+    assert_eq!(
+        git.runner.take_args(),
+        args(&[&["remote", "add", "upstream", "https://example.com/repo.git"]])
+    );
+}
+
+#[test]
+fn remote_edits_require_input_and_removal_is_confirmed() {
+    let mut git = git();
+    git.remotes_tab.remotes = Remote::parse_list(
+        "origin\thttps://example.com/repo.git (fetch)\norigin\thttps://example.com/repo.git (push)",
+    )
+    .unwrap();
+    git.remotes_tab.table_state.select_index(0);
+    git.branch_type = BranchType::Remotes;
+
+    assert!(git.rename_remote().is_err());
+    assert!(git.set_remote_url().is_err());
+    git.remotes_tab.input = LineInput::from("upstream");
+    git.rename_remote().unwrap();
+    assert_eq!(
+        git.runner.take_args(),
+        args(&[&["remote", "rename", "origin", "upstream"]])
+    );
+
+    git.update(ctrl('d'));
+    assert_eq!(git.remotes_tab.removal.as_deref(), Some("origin"));
+    git.update(key(BareKey::Esc));
+    assert_eq!(git.remotes_tab.removal, None);
+    assert!(git.runner.take_args().is_empty());
+
+    git.update(ctrl('d'));
+    git.update(key(BareKey::Char('y')));
+    assert_eq!(git.remotes_tab.removal, None);
+    assert_eq!(
+        git.runner.take_args(),
+        args(&[&["remote", "remove", "origin"]])
+    );
+}
+
+#[test]
+fn unreadable_command_context_is_reported() {
+    let mut git = git();
+    let context = BTreeMap::from([(String::from("command"), String::from("unknown"))]);
+
+    assert!(git.update(Event::RunCommandResult(Some(0), vec![], vec![], context)));
+    assert!(git.messages.is_open());
+}
+
+#[test]
+fn selection_stays_within_the_list() {
+    let mut git = loaded_git();
+
+    for _ in 0..5 {
+        git.update(key(BareKey::Down));
+    }
+    assert_eq!(
+        git.local_branches_tab.selected_branch_name().as_deref(),
+        Some("topic")
+    );
+    git.update(key(BareKey::Up));
+    assert_eq!(
+        git.local_branches_tab.selected_branch_name().as_deref(),
+        Some("main")
+    );
+}
+
+#[test]
+fn superseded_refreshes_are_detected() {
+    let mut git = git();
+    git.list_local_branches(false);
+    git.list_local_branches(false);
+    git.switch_to_previous_branch();
+
+    let invocations = git.runner.take_invocations();
+    assert!(git.is_superseded(&invocations[0].context));
+    assert!(!git.is_superseded(&invocations[1].context));
+    assert!(!git.is_superseded(&invocations[2].context));
+}
+
+#[test]
+fn refresh_selects_current_branch() {
+    let git = loaded_git();
+
+    assert_eq!(branch_names(&git), ["feature", "main", "topic"]);
+    assert_eq!(
+        git.local_branches_tab.selected_branch_name().as_deref(),
+        Some("main")
+    );
+}
+
+#[test]
+fn superseded_refresh_results_are_dropped() {
+    let mut git = git();
+    git.list_local_branches(false);
+    git.list_local_branches(false);
+    let stale = git.runner.pop_invocation().unwrap();
+
+    complete(&mut git, 0, "* main 2222222 Initial commit\n", "");
+    let rerender = git.update(Event::RunCommandResult(
+        Some(0),
+        LOCAL_BRANCHES.as_bytes().to_vec(),
+        Vec::new(),
+        BTreeMap::from(stale.context),
+    ));
+
+    assert!(!rerender);
+    assert_eq!(branch_names(&git), ["main"]);
+}
+
+#[test]
+fn failed_command_opens_message_panel() {
+    let mut git = loaded_git();
+    git.update(key(BareKey::Down));
+    git.update(key(BareKey::Enter));
+    complete(
+        &mut git,
+        1,
+        "",
+        "error: Your local changes would be overwritten\n",
+    );
+    assert!(git.messages.is_open());
+    assert_eq!(
+        git.messages.latest(),
+        Some(&Message {
+            level: Level::Error,
+            title: String::from("switch topic failed with exit code 1"),
+            body: String::from("error: Your local changes would be overwritten"),
+        })
+    );
+
+    git.update(key(BareKey::Char('f')));
+    assert_eq!(git.local_branches_tab.input.as_str(), "f");
+    assert!(git.messages.is_open());
+
+    git.update(key(BareKey::Esc));
+    assert!(!git.messages.is_open());
+    assert!(!git.host.closed.get());
+
+    git.update(Event::Key(
+        KeyWithModifier::new(BareKey::Char('m')).with_alt_modifier(),
+    ));
+    assert!(git.messages.is_open());
+}
+
+#[test]
+fn successful_action_refreshes_local_branches() {
+    let mut git = loaded_git();
+
+    git.update(ctrl('d'));
+    assert!(complete(
+        &mut git,
+        0,
+        "Deleted branch main (was 2222222).\n",
+        ""
+    ));
+
+    assert_eq!(
+        git.runner.take_invocations()[0].context.command,
+        Command::ListLocalBranches {
+            preserve_selection: true
+        }
+    );
+}
+
+#[test]
+fn successful_action_reports_status() {
+    let mut git = loaded_git();
+    git.update(key(BareKey::Down));
+
+    git.update(ctrl('d'));
+    complete(&mut git, 0, "Deleted branch topic (was 3333333).\n", "");
+    assert_eq!(
+        git.messages.status().map(|status| status.content),
+        Some(String::from("Deleted topic (was 3333333)"))
+    );
+    assert!(
+        git.host
+            .timeouts
+            .borrow()
+            .contains(&Messages::STATUS_SECONDS)
+    );
+    git.runner.take_invocations();
+
+    git.update(key(BareKey::Enter));
+    complete(
+        &mut git,
+        0,
+        "",
+        "Switched to branch 'topic'\nYour branch is up to date.\n",
+    );
+    assert_eq!(
+        git.messages.latest(),
+        Some(&Message {
+            level: Level::Info,
+            title: String::from("Switched to topic"),
+            body: String::from("Switched to branch 'topic'\nYour branch is up to date."),
+        })
+    );
+    assert!(!git.messages.is_open());
+}
+
+#[test]
+fn deleted_branches_are_restored_in_reverse_order() {
+    let mut git = loaded_git();
+    git.update(ctrl('x'));
+    complete(&mut git, 0, "Deleted branch main (was 2222222).\n", "");
+    git.runner.take_invocations();
+    git.update(key(BareKey::Down));
+    git.update(ctrl('x'));
+    complete(&mut git, 0, "Deleted branch topic (was 3333333).\n", "");
+    git.runner.take_invocations();
+
+    git.update(ctrl('z'));
+    complete(
+        &mut git,
+        128,
+        "",
+        "fatal: a branch named 'topic' already exists",
+    );
+    git.update(ctrl('z'));
+    git.update(ctrl('z'));
+    assert!(!git.update(ctrl('z')));
+
+    assert_eq!(
+        git.runner.take_args(),
+        args(&[
+            &["branch", "topic", "3333333"],
+            &["branch", "main", "2222222"],
+        ])
+    );
+}
+
+#[test]
+fn current_branch_is_reset_to_selected_branch() {
+    let mut git = loaded_git();
+    git.update(key(BareKey::Up));
+
+    git.update(ctrl('e'));
+    git.update(key(BareKey::Char('m')));
+    assert_eq!(
+        git.runner.take_args(),
+        args(&[&["reset", "--mixed", "feature"]])
+    );
+    assert!(git.reset_prompt.is_none());
+
+    git.update(ctrl('e'));
+    git.update(key(BareKey::Char('h')));
+    let invocation = git.runner.pop_invocation().unwrap();
+    assert_eq!(invocation.args, ["rev-list", "--count", "feature..main"]);
+    respond(&mut git, invocation, 0, "2\n", "");
+    assert_eq!(
+        git.reset_prompt
+            .as_ref()
+            .map(|reset_prompt| reset_prompt.state),
+        Some(ResetState::Confirming { lost_commits: 2 })
+    );
+
+    git.update(key(BareKey::Char('y')));
+    assert_eq!(
+        git.runner.take_args(),
+        args(&[&["reset", "--hard", "feature"]])
+    );
+}
+
+#[test]
+fn branch_is_moved_to_upstream_when_no_commits_are_lost() {
+    let mut git = loaded_git();
+    git.update(key(BareKey::Up));
+
+    git.update(Event::Key(
+        KeyWithModifier::new(BareKey::Char('e')).with_alt_modifier(),
+    ));
+    complete(&mut git, 0, "0\n", "");
+
+    assert_eq!(
+        git.runner.take_args(),
+        args(&[&["branch", "-f", "feature", "origin/feature"]])
+    );
+}
+
+const COMMITS: &str = "\
+4444444\tAda\tFix typo
+5555555\tGrace\tAdd tests
+6666666\tAda\tAdd parser
+";
+
+fn alt(c: char) -> Event {
+    Event::Key(KeyWithModifier::new(BareKey::Char(c)).with_alt_modifier())
+}
+
+#[test]
+fn marked_commits_are_cherry_picked_oldest_first() {
+    let mut git = loaded_git();
+    git.update(key(BareKey::Up));
+
+    git.update(alt('c'));
+    let invocation = git.runner.pop_invocation().unwrap();
+    assert_eq!(
+        invocation.args,
+        ["log", "--format=%h%x09%an%x09%s", "HEAD..feature"]
+    );
+    respond(&mut git, invocation, 0, COMMITS, "");
+    git.update(key(BareKey::Tab));
+    git.update(key(BareKey::Down));
+    git.update(key(BareKey::Tab));
+    git.update(key(BareKey::Enter));
+
+    assert!(git.commit_picker.is_none());
+    assert_eq!(
+        git.runner.take_args(),
+        args(&[&["cherry-pick", "6666666", "4444444"]])
+    );
+}
+
+#[test]
+fn commit_picker_filters_by_message() {
+    let mut git = loaded_git();
+    git.update(alt('c'));
+    complete(&mut git, 0, COMMITS, "");
+
+    type_text(&mut git, "add");
+    git.update(key(BareKey::Enter));
+
+    assert_eq!(git.runner.take_args(), args(&[&["cherry-pick", "5555555"]]));
+}
+
+#[test]
+fn cherry_pick_conflict_is_resolved_in_plugin() {
+    let mut git = loaded_git();
+    git.update(alt('c'));
+    complete(&mut git, 0, COMMITS, "");
+    git.update(key(BareKey::Enter));
+    complete(
+        &mut git,
+        1,
+        "CONFLICT (content): Merge conflict in src/main.rs\n",
+        "error: could not apply 4444444... Fix typo\n",
+    );
+    assert!(!git.cherry_pick_conflict);
+    let check = git.runner.pop_invocation().unwrap();
+    assert_eq!(
+        check.args,
+        ["rev-parse", "-q", "--verify", "CHERRY_PICK_HEAD"]
+    );
+    respond(&mut git, check, 0, "4444444\n", "");
+    assert!(git.cherry_pick_conflict);
+
+    git.update(key(BareKey::Char('c')));
+    complete(
+        &mut git,
+        1,
+        "",
+        "error: Committing is not possible because you have unmerged files.\n",
+    );
+    complete(&mut git, 0, "4444444\n", "");
+    assert!(git.cherry_pick_conflict);
+
+    git.update(key(BareKey::Char('a')));
+    complete(&mut git, 0, "", "");
+    assert!(!git.cherry_pick_conflict);
+    assert_eq!(
+        git.messages.latest().map(|message| message.title.as_str()),
+        Some("Aborted the cherry-pick")
+    );
+}
+
+#[test]
+fn cherry_pick_state_follows_repository() {
+    let mut git = loaded_git();
+    git.branch_type = BranchType::Commits;
+
+    // Started outside of the plugin
+    git.check_cherry_pick();
+    complete(&mut git, 0, "4444444\n", "");
+    assert!(git.cherry_pick_conflict);
+    assert_eq!(git.branch_type, BranchType::Local);
+
+    // Hidden prompts stay hidden while the cherry-pick is in progress
+    git.update(key(BareKey::Esc));
+    git.check_cherry_pick();
+    complete(&mut git, 0, "4444444\n", "");
+    assert!(!git.cherry_pick_conflict);
+
+    // Until they are opened again
+    git.update(alt('c'));
+    assert!(git.cherry_pick_conflict);
+    assert!(git.commit_picker.is_none());
+    git.update(key(BareKey::Char('s')));
+    assert_eq!(git.runner.take_args(), args(&[&["cherry-pick", "--skip"]]));
+
+    // Resolved outside of the plugin
+    git.cherry_pick_conflict = true;
+    git.check_cherry_pick();
+    complete(&mut git, 1, "", "");
+    assert!(!git.cherry_pick_conflict);
+    assert!(!git.messages.is_open());
+}
+
+#[test]
+fn filtering_commits_pages_by_position_in_all_commits() {
+    let page: String = (0..CommitsTab::PAGE_SIZE)
+        .map(|i| format!("{i:07x}\tAda\tCommit {i}\n"))
+        .collect();
+    let mut git = commits_git(&page);
+
+    // Few commits match, but the selected one is far from the end of the loaded commits
+    type_text(&mut git, "Commit 1");
+    assert!(git.runner.take_args().is_empty());
+
+    type_text(&mut git, "9");
+    assert_eq!(
+        git.commits_tab.commits.selected_branch_name().as_deref(),
+        Some("0000013")
+    );
+    assert!(git.runner.take_args().is_empty());
+
+    git.commits_tab.commits.set_filter("Commit 99");
+    git.load_more_commits();
+    assert_eq!(git.runner.take_args().len(), 1);
+}
+
+#[test]
+fn refreshing_commits_keeps_loaded_pages() {
+    let commits = |range: std::ops::Range<usize>| -> String {
+        range
+            .map(|i| format!("{i:07x}\tAda\tCommit {i}\n"))
+            .collect()
+    };
+    let mut git = commits_git(&commits(0..CommitsTab::PAGE_SIZE));
+    git.list_log(CommitsTab::PAGE_SIZE, CommitsTab::PAGE_SIZE);
+    complete(&mut git, 0, &commits(100..200), "");
+    git.commits_tab.commits.select_branch_by_name("000009b");
+
+    git.refresh();
+    let invocation = git.runner.pop_invocation().unwrap();
+    assert_eq!(
+        invocation.context.command,
+        Command::ListLog {
+            skip: 0,
+            count: 200
+        }
+    );
+    // A commit made elsewhere is listed first
+    let log = format!("fffffff\tAda\tNew commit\n{}", commits(0..199));
+    respond(&mut git, invocation, 0, &log, "");
+    assert_eq!(git.commits_tab.commits.view.branches.len(), 200);
+    assert_eq!(
+        git.commits_tab.commits.selected_branch_name().as_deref(),
+        Some("000009b")
+    );
+}
+
+fn commits_git(log: &str) -> TestGit {
+    let mut git = git();
+    git.branch_type = BranchType::Commits;
+    git.commits_tab.commits.inited = true;
+    git.list_log(0, CommitsTab::PAGE_SIZE);
+    complete(&mut git, 0, log, "");
+    git
+}
+
+#[test]
+fn commits_are_loaded_a_page_at_a_time() {
+    let page: String = (0..CommitsTab::PAGE_SIZE)
+        .map(|i| format!("{i:07x}\tAda\tCommit {i}\n"))
+        .collect();
+    let mut git = commits_git(&page);
+
+    for _ in 0..80 {
+        git.update(key(BareKey::Down));
+    }
+    assert!(git.runner.take_args().is_empty());
+    for _ in 0..10 {
+        git.update(key(BareKey::Down));
+    }
+    let invocation = git.runner.pop_invocation().unwrap();
+    assert_eq!(
+        invocation.args,
+        [
+            "log",
+            "--all",
+            "--format=%h%x09%an%x09%s",
+            "--skip=100",
+            "-n",
+            "100"
+        ]
+    );
+    respond(&mut git, invocation, 0, COMMITS, "");
+    assert_eq!(git.commits_tab.commits.view.branches.len(), 103);
+    assert!(git.commits_tab.exhausted);
+
+    git.update(key(BareKey::Down));
+    assert!(git.runner.take_args().is_empty());
+    assert_eq!(
+        git.commits_tab.commits.selected_branch_name().as_deref(),
+        Some("000005b")
+    );
+}
+
+#[test]
+fn commit_actions_produce_git_arguments() {
+    let mut git = commits_git(COMMITS);
+
+    git.update(key(BareKey::Enter));
+    git.update(ctrl('c'));
+    type_text(&mut git, "hotfix");
+    git.update(key(BareKey::Enter));
+    git.update(ctrl('t'));
+    type_text(&mut git, "v1.0");
+    git.update(key(BareKey::Enter));
+    git.update(ctrl('t'));
+    git.update(key(BareKey::Esc));
+
+    assert!(git.name_prompt.is_none());
+    assert_eq!(
+        git.runner.take_args(),
+        args(&[
+            &["switch", "--detach", "4444444"],
+            &["branch", "hotfix", "4444444"],
+            &["tag", "v1.0", "4444444"],
+        ])
+    );
+}
+
+#[test]
+fn commits_are_filtered_by_author() {
+    let mut git = commits_git(COMMITS);
+
+    type_text(&mut git, "author:grace");
+    assert_eq!(
+        git.commits_tab.commits.selected_branch_name().as_deref(),
+        Some("5555555")
+    );
+
+    git.update(ctrl('l'));
+    let command_panes = git.host.command_panes.borrow();
+    assert_eq!(command_panes.len(), 1);
+    assert_eq!(command_panes[0].0.args, ["show", "5555555"]);
+}
+
+const DETACHED_BRANCHES: &str = "\
+* (HEAD detached at 2222222) 2222222 Initial commit
+  main                       2222222 [origin/main] Initial commit
+";
+
+fn detached_git() -> TestGit {
+    let mut git = git();
+    git.local_branches_tab.inited = true;
+    git.list_local_branches(false);
+    complete(&mut git, 0, DETACHED_BRANCHES, "");
+    git
+}
+
+#[test]
+fn detached_head_is_not_a_branch() {
+    let mut git = detached_git();
+    let detached_head = git.local_branches_tab.selected_branch().unwrap();
+    assert!(detached_head.detached && detached_head.current);
+    assert_eq!(detached_head.name, "HEAD detached at 2222222");
+    assert_eq!(detached_head.rev(), "HEAD");
+
+    git.update(key(BareKey::Enter));
+    git.update(ctrl('d'));
+    assert!(git.runner.take_args().is_empty());
+
+    git.update(alt('n'));
+    type_text(&mut git, "rescued");
+    git.update(key(BareKey::Enter));
+    assert_eq!(
+        git.runner.take_args(),
+        args(&[&["checkout", "-b", "rescued"]])
+    );
+
+    git.update(key(BareKey::Down));
+    git.update(alt('n'));
+    assert!(git.name_prompt.is_none());
+}
+
+#[test]
+fn alt_b_moves_cursor_on_detached_head() {
+    let mut git = detached_git();
+    type_text(&mut git, "head det");
+    assert!(git.local_branches_tab.selected_branch().unwrap().detached);
+
+    git.update(alt('b'));
+    assert!(git.name_prompt.is_none());
+    git.update(key(BareKey::Char('a')));
+    assert_eq!(git.local_branches_tab.input.as_str(), "head adet");
+}
+
+#[test]
+fn filter_is_kept_after_refresh() {
+    let mut git = loaded_git();
+
+    type_text(&mut git, "fe");
+    assert_eq!(branch_names(&git), ["feature"]);
+
+    git.list_local_branches(false);
+    complete(
+        &mut git,
+        0,
+        &format!("{LOCAL_BRANCHES}  fenix 4444444 Rise again\n"),
+        "",
+    );
+    assert_eq!(git.local_branches_tab.input.as_str(), "fe");
+    assert_eq!(branch_names(&git).len(), 2);
+    assert!(branch_names(&git).contains(&"fenix"));
+
+    git.update(key(BareKey::Backspace));
+    git.update(key(BareKey::Backspace));
+    assert!(git.local_branches_tab.filtered_view.is_none());
+    assert_eq!(branch_names(&git).len(), 4);
+}
+
+#[test]
+fn filter_is_edited_at_cursor() {
+    let mut git = loaded_git();
+
+    type_text(&mut git, "mn");
+    git.update(key(BareKey::Left));
+    type_text(&mut git, "ai");
+    assert_eq!(git.local_branches_tab.input.as_str(), "main");
+    assert_eq!(branch_names(&git), ["main"]);
+
+    git.update(ctrl('w'));
+    assert_eq!(git.local_branches_tab.input.as_str(), "n");
+
+    git.update(key(BareKey::End));
+    git.update(ctrl('w'));
+    assert!(git.local_branches_tab.filtered_view.is_none());
+    assert_eq!(branch_names(&git).len(), 3);
+}
+
+#[test]
+fn pasted_text_filters_without_running_actions() {
+    let mut git = loaded_git();
+
+    git.update(Event::PastedText(String::from("feat\n")));
+    assert_eq!(git.local_branches_tab.input.as_str(), "feat");
+    assert_eq!(branch_names(&git), ["feature"]);
+    assert!(git.runner.take_args().is_empty());
+
+    git.update(ctrl('t'));
+    git.update(Event::PastedText(String::from("origin\r\n")));
+    assert_eq!(
+        git.upstream_picker.as_ref().unwrap().tab.input.as_str(),
+        "origin"
+    );
+    assert_eq!(git.local_branches_tab.input.as_str(), "feat");
+}
+
+#[test]
+fn filter_keeps_matched_characters() {
+    let mut git = loaded_git();
+
+    type_text(&mut git, "ft");
+    assert_eq!(branch_names(&git), ["feature"]);
+    assert_eq!(match_indices(&git), [vec![0, 3]]);
+
+    type_text(&mut git, "r");
+    assert_eq!(match_indices(&git), [vec![0, 3, 5]]);
+}
+
+#[test]
+fn selection_is_kept_across_refreshes() {
+    let mut git = loaded_git();
+    git.update(key(BareKey::Down));
+    assert_eq!(
+        git.local_branches_tab.selected_branch_name().as_deref(),
+        Some("topic")
+    );
+
+    git.update(ctrl('r'));
+    complete(
+        &mut git,
+        0,
+        &format!("  alpha 0000000 First\n{LOCAL_BRANCHES}"),
+        "",
+    );
+    assert_eq!(
+        git.local_branches_tab.selected_branch_name().as_deref(),
+        Some("topic")
+    );
+
+    git.update(ctrl('d'));
+    complete(&mut git, 0, "Deleted branch topic (was 3333333).\n", "");
+    complete(
+        &mut git,
+        0,
+        "  alpha 0000000 First\n  feature 1111111 Add feature\n* main 2222222 Initial commit\n",
+        "",
+    );
+    assert_eq!(
+        git.local_branches_tab.selected_branch_name().as_deref(),
+        Some("main")
+    );
+}
+
+#[test]
+fn selection_is_kept_across_filter_edits() {
+    let mut git = loaded_git();
+    git.update(key(BareKey::Down));
+
+    type_text(&mut git, "t");
+    assert_eq!(branch_names(&git), ["topic", "feature"]);
+    assert_eq!(
+        git.local_branches_tab.selected_branch_name().as_deref(),
+        Some("topic")
+    );
+
+    git.update(key(BareKey::Backspace));
+    assert_eq!(
+        git.local_branches_tab.selected_branch_name().as_deref(),
+        Some("topic")
+    );
+
+    type_text(&mut git, "fe");
+    assert_eq!(
+        git.local_branches_tab.selected_branch_name().as_deref(),
+        Some("feature")
+    );
+    git.update(ctrl('u'));
+    assert_eq!(
+        git.local_branches_tab.selected_branch_name().as_deref(),
+        Some("feature")
+    );
+}
+
+#[test]
+fn tab_cycles_through_tabs() {
+    let mut git = loaded_git();
+
+    git.update(key(BareKey::Tab));
+    assert!(git.branch_type == BranchType::Remote);
+    git.update(key(BareKey::Tab));
+    assert!(git.branch_type == BranchType::Remotes);
+    git.update(key(BareKey::Tab));
+    assert!(git.branch_type == BranchType::Commits);
+    git.update(key(BareKey::Tab));
+    assert!(git.branch_type == BranchType::Local);
+}
+
+#[test]
+fn remote_tab_tracks_selected_branch() {
+    let mut git = loaded_git();
+    git.update(key(BareKey::Tab));
+    git.list_remote_branches(false);
+    complete(
+        &mut git,
+        0,
+        "  origin/main    2222222 Initial commit\n  origin/release 5555555 Prepare release\n",
+        "",
+    );
+
+    type_text(&mut git, "rel");
+    git.update(key(BareKey::Enter));
+    assert_eq!(
+        git.runner.take_args(),
+        args(&[&["checkout", "--track", "origin/release"]])
+    );
+}
+
+#[test]
+fn remote_head_tracks_default_branch() {
+    let mut git = loaded_git();
+    git.update(key(BareKey::Tab));
+    git.list_remote_branches(false);
+    complete(
+        &mut git,
+        0,
+        "  upstream/HEAD    -> upstream/develop\n  upstream/develop 5555555 Start development\n",
+        "",
+    );
+
+    git.update(key(BareKey::Enter));
+    git.update(alt('d'));
+    assert_eq!(
+        git.runner.take_args(),
+        args(&[
+            &["checkout", "--track", "upstream/develop"],
+            &["remote", "set-head", "upstream", "--auto"],
+        ])
+    );
+}
+
+#[test]
+fn tracking_existing_branch_offers_choices() {
+    let mut git = loaded_git();
+    git.list_remote_branches(false);
+    complete(&mut git, 0, REMOTE_BRANCHES, "");
+    git.update(key(BareKey::Tab));
+    type_text(&mut git, "feat");
+
+    git.update(key(BareKey::Enter));
+    assert!(git.runner.take_args().is_empty());
+    git.update(key(BareKey::Char('s')));
+    assert!(git.branch_type == BranchType::Local);
+    assert_eq!(git.runner.take_args(), args(&[&["switch", "feature"]]));
+
+    git.update(key(BareKey::Tab));
+    git.update(key(BareKey::Enter));
+    git.update(key(BareKey::Char('r')));
+    assert!(git.branch_type == BranchType::Local);
+    assert_eq!(
+        git.reset_prompt
+            .as_ref()
+            .map(|reset_prompt| reset_prompt.state),
+        Some(ResetState::CountingCommits)
+    );
+    assert_eq!(
+        git.runner.take_args(),
+        args(&[&["rev-list", "--count", "origin/feature..feature"]])
+    );
+    git.update(key(BareKey::Esc));
+
+    git.update(key(BareKey::Tab));
+    git.update(key(BareKey::Enter));
+    git.update(key(BareKey::Char('n')));
+    type_text(&mut git, "feature-2");
+    git.update(key(BareKey::Enter));
+    assert_eq!(
+        git.runner.take_args(),
+        args(&[&["checkout", "-b", "feature-2", "--track", "origin/feature"]])
+    );
+}
+
+#[test]
+fn esc_cancels_upstream_picker_before_closing() {
+    let mut git = loaded_git();
+    git.remote_branches_tab.inited = true;
+    git.list_remote_branches(false);
+    complete(&mut git, 0, REMOTE_BRANCHES, "");
+
+    git.update(ctrl('t'));
+    assert!(git.upstream_picker.is_some());
+    type_text(&mut git, "feat");
+    git.update(key(BareKey::Enter));
+    assert!(git.upstream_picker.is_none());
+    assert_eq!(
+        git.runner.take_args(),
+        args(&[&["branch", "--set-upstream-to=origin/feature", "main"]])
+    );
+
+    git.update(ctrl('t'));
+    git.update(key(BareKey::Esc));
+    assert!(git.upstream_picker.is_none());
+    assert!(!git.host.closed.get());
+
+    git.update(key(BareKey::Esc));
+    assert!(git.host.closed.get());
+}
+
+#[test]
+fn repository_changes_trigger_debounced_refresh() {
+    let mut git = loaded_git();
+    let event =
+        || Event::FileSystemUpdate(vec![(PathBuf::from("/host/.git/refs/heads/main"), None)]);
+
+    git.update(event());
+    git.update(event());
+    git.update(Event::FileSystemUpdate(vec![(
+        PathBuf::from("/host/src/main.rs"),
+        None,
+    )]));
+    assert_eq!(git.host.timeouts.borrow().len(), 1);
+    assert!(git.runner.take_invocations().is_empty());
+
+    // A spinner tick before the deadline doesn't refresh
+    git.update(Event::Timer(0.1));
+    assert!(git.runner.take_invocations().is_empty());
+
+    git.refresh_at = git
+        .refresh_at
+        .map(|refresh_at| refresh_at - Duration::from_secs(1));
+    git.update(Event::Timer(0.5));
+    assert_eq!(
+        git.runner.take_invocations()[0].context.command,
+        Command::ListLocalBranches {
+            preserve_selection: true
+        }
+    );
+}
+
+#[test]
+fn cwd_pipe_message_changes_repository() {
+    let mut git = loaded_git();
+    git.remote_branches_tab.inited = true;
+    git.remotes_tab.inited = true;
+    git.commits_tab.commits.inited = true;
+    git.deleted_branches.push(DeletedBranch {
+        name: String::from("topic"),
+        sha: String::from("3333333"),
+    });
+    git.cherry_pick_in_progress = true;
+    git.notice = Some(String::from("Pruned origin/old"));
+    git.last_fetch = Some(Instant::now());
+    git.fetch_failed = true;
+    git.runner.take_invocations();
+
+    git.pipe(PipeMessage {
+        source: PipeSource::Keybind,
+        name: String::from("cwd"),
+        payload: Some(String::from("/other")),
+        args: BTreeMap::new(),
+        is_private: false,
+    });
+
+    assert_eq!(*git.host.host_folders.borrow(), [PathBuf::from("/other")]);
+    assert!(git.deleted_branches.is_empty());
+    assert!(!git.cherry_pick_in_progress);
+    assert_eq!(git.notice, None);
+    assert_eq!(git.last_fetch, None);
+    assert!(!git.fetch_failed);
+    let invocations = git.runner.take_invocations();
+    assert_eq!(
+        invocations
+            .iter()
+            .map(|invocation| invocation.context.command.clone())
+            .collect::<Vec<_>>(),
+        [
+            Command::ListLocalBranches {
+                preserve_selection: false
+            },
+            Command::CheckCherryPick,
+            Command::ListRemoteBranches {
+                preserve_selection: false
+            },
+            Command::ListRemotes,
+            Command::ListLog {
+                skip: 0,
+                count: CommitsTab::PAGE_SIZE
+            },
+        ]
+    );
+    assert!(
+        invocations
+            .iter()
+            .all(|invocation| invocation.cwd == Some(PathBuf::from("/other")))
+    );
+}
+
+fn cli_pipe(git: &mut TestGit, name: &str, payload: &str) {
+    git.pipe(PipeMessage {
+        source: PipeSource::Cli(String::from("pipe-1")),
+        name: String::from(name),
+        payload: Some(String::from(payload)),
+        args: BTreeMap::new(),
+        is_private: false,
+    });
+}
+
+fn pipe_replies(git: &TestGit) -> Vec<String> {
+    git.host
+        .pipe_replies
+        .take()
+        .into_iter()
+        .map(|(_, reply)| reply)
+        .collect()
+}
+
+#[test]
+fn pipe_commands_run_key_actions() {
+    let mut git = loaded_git();
+
+    cli_pipe(&mut git, "switch", "feature");
+    cli_pipe(&mut git, "create", "hotfix main");
+    assert_eq!(
+        git.runner.take_args(),
+        args(&[
+            &["switch", "feature"],
+            &["checkout", "-b", "hotfix", "main"]
+        ])
+    );
+
+    cli_pipe(&mut git, "tab", "commits");
+    assert!(git.branch_type == BranchType::Commits);
+    cli_pipe(&mut git, "tab", "local");
+    cli_pipe(&mut git, "filter", "top");
+    assert_eq!(
+        git.local_branches_tab.selected_branch_name().as_deref(),
+        Some("topic")
+    );
+    assert_eq!(
+        pipe_replies(&git),
+        [
+            "Switched to the commits tab\n",
+            "Switched to the local tab\n",
+            "Filtering by top\n"
+        ]
+    );
+
+    cli_pipe(&mut git, "refresh", "");
+    cli_pipe(&mut git, "filter", "");
+    assert_eq!(
+        pipe_replies(&git),
+        ["Reloading the lists\n", "Cleared the filter\n"]
+    );
+}
+
+#[test]
+fn pipe_commands_wait_for_open_prompts() {
+    let mut git = loaded_git();
+    git.update(alt('c'));
+
+    cli_pipe(&mut git, "tab", "commits");
+    cli_pipe(&mut git, "refresh", "");
+    assert_eq!(git.branch_type, BranchType::Local);
+    assert_eq!(
+        pipe_replies(&git),
+        [
+            "Error: A prompt is open in the plugin, answer or cancel it first\n",
+            "Reloading the lists\n"
+        ]
+    );
+}
+
+#[test]
+fn pipe_replies_when_git_finishes() {
+    let mut git = loaded_git();
+
+    cli_pipe(&mut git, "switch", "feature");
+    assert_eq!(*git.host.blocked_pipes.borrow(), ["pipe-1"]);
+    complete(&mut git, 0, "", "");
+    assert_eq!(pipe_replies(&git), ["Switched to feature\n"]);
+    git.runner.take_invocations();
+
+    cli_pipe(&mut git, "switch", "topic");
+    complete(
+        &mut git,
+        1,
+        "",
+        "error: Your local changes would be overwritten by checkout\n",
+    );
+    assert_eq!(
+        pipe_replies(&git),
+        [
+            "Error: switch topic failed with exit code 1\nerror: Your local changes would be overwritten by checkout\n"
+        ]
+    );
+}
+
+#[test]
+fn invalid_pipe_commands_are_reported() {
+    let mut git = loaded_git();
+
+    cli_pipe(&mut git, "switch", "nope");
+    cli_pipe(&mut git, "create", "");
+    cli_pipe(&mut git, "create", "main");
+    cli_pipe(&mut git, "tab", "branches");
+    // Messages meant for other plugins are ignored
+    assert!(!git.pipe(PipeMessage {
+        source: PipeSource::Cli(String::from("pipe-1")),
+        name: String::from("merge"),
+        payload: None,
+        args: BTreeMap::new(),
+        is_private: false,
+    }));
+    git.pipe(PipeMessage {
+        source: PipeSource::Cli(String::from("pipe-2")),
+        name: String::from("merge"),
+        payload: None,
+        args: BTreeMap::new(),
+        is_private: true,
+    });
+    assert!(git.runner.take_args().is_empty());
+    assert_eq!(
+        pipe_replies(&git),
+        [
+            "Error: No local branch named nope\n",
+            "Error: Usage: create <name> [start]\n",
+            "Error: A local branch named main already exists\n",
+            "Error: Unknown tab: branches. Expected local|remote|remotes|commits\n",
+            "Error: Unknown command: merge\n",
+        ]
+    );
+
+    git.pipe(PipeMessage {
+        source: PipeSource::Keybind,
+        name: String::from("checkout-remote"),
+        payload: Some(String::from("feature")),
+        args: BTreeMap::new(),
+        is_private: false,
+    });
+    assert_eq!(
+        git.messages.latest().map(|message| message.title.as_str()),
+        Some("Usage: checkout-remote <remote/branch>")
+    );
+}
+
+#[test]
+fn pipe_commands_load_local_branches_first() {
+    for (name, payload) in [("switch", "feature"), ("create", "hotfix")] {
+        let mut git = git();
+
+        cli_pipe(&mut git, name, payload);
+
+        assert!(git.local_branches_tab.inited);
+        assert_eq!(
+            git.runner.take_args(),
+            [
+                vec!["branch", "-vv"],
+                vec!["rev-parse", "-q", "--verify", "CHERRY_PICK_HEAD"],
+            ]
+        );
+        assert_eq!(
+            pipe_replies(&git),
+            ["Error: Local branches are not loaded yet, try again\n"]
+        );
+    }
+}
@@ -0,0 +1,529 @@
+use zellij_tile::prelude::*;
+
+use super::{
+    COMMITS, REMOTE_BRANCHES, TestGit, alt, commits_git, complete, ctrl, detached_git, git, key,
+    loaded_git,
+};
+use crate::canvas::GridCanvas;
+
+fn snapshot(lines: &[&str]) -> String {
+    lines.join("\n")
+}
+
+fn render(git: &mut TestGit, rows: usize, cols: usize) -> String {
+    let mut canvas = GridCanvas::new(rows, cols);
+    git.render_to(&mut canvas, rows, cols);
+    canvas.snapshot()
+}
+
+#[test]
+fn local_branches() {
+    let mut git = loaded_git();
+
+    assert_eq!(
+        render(&mut git, 13, 100),
+        snapshot(&[
+            "[ Local ]( Remote )( Remotes )( Commits )",
+            "",
+            " Branch: |",
+            "",
+            " Name     Upstream                 Sha      Message",
+            " feature  origin/feature: ahead 1  1111111  Add feature",
+            " main     origin/main              2222222  Initial commit",
+            "",
+            "<Ctrl-r> - Refresh, <Ctrl-c> - Create, <Ctrl-d> - Delete, <Ctrl-x> - Force delete",
+            "<Ctrl-z> - Undo delete, <Ctrl-l> - Open log, <Ctrl-p> - Previous branch, <Ctrl-f> - Fetch",
+            "<Ctrl-t> - Set upstream, <Alt-t> - Unset upstream, <Ctrl-e> - Reset to selected",
+            "<Alt-e> - Reset to upstream, <Alt-c> - Cherry-pick, <Alt-n> - Branch from HEAD, <Alt-m> - Messages",
+            "/repo",
+        ])
+    );
+}
+
+#[test]
+fn local_branches_narrow() {
+    let mut git = loaded_git();
+
+    assert_eq!(
+        render(&mut git, 10, 40),
+        snapshot(&[
+            "[ Local ]( Remote )( Remotes )( Commits",
+            "",
+            " Branch: |",
+            "",
+            " Name     Upstream         Sha  Message",
+            " feature  origin/feature:  111  Add fea",
+            " main     origin/main      222  Initial",
+            " topic                     333  Work in",
+            "",
+            "/repo",
+        ])
+    );
+}
+
+#[test]
+fn filtered_local_branches() {
+    let mut git = loaded_git();
+    git.update(key(BareKey::Char('t')));
+    git.update(key(BareKey::Char('o')));
+
+    assert_eq!(
+        render(&mut git, 13, 80),
+        snapshot(&[
+            "[ Local ]( Remote )( Remotes )( Commits )",
+            "",
+            " Branch: to|",
+            "",
+            " Name   Upstream  Sha      Message",
+            " topic            3333333  Work in progress",
+            "<Ctrl-r> - Refresh, <Ctrl-c> - Create, <Ctrl-d> - Delete",
+            "<Ctrl-x> - Force delete, <Ctrl-z> - Undo delete, <Ctrl-l> - Open log",
+            "<Ctrl-p> - Previous branch, <Ctrl-f> - Fetch, <Ctrl-t> - Set upstream",
+            "<Alt-t> - Unset upstream, <Ctrl-e> - Reset to selected",
+            "<Alt-e> - Reset to upstream, <Alt-c> - Cherry-pick, <Alt-n> - Branch from HEAD",
+            "<Alt-m> - Messages",
+            "/repo",
+        ])
+    );
+}
+
+#[test]
+fn remote_branches() {
+    let mut git = loaded_git();
+    git.update(key(BareKey::Tab));
+    git.remote_branches_tab.inited = true;
+    git.list_remote_branches(false);
+    complete(&mut git, 0, REMOTE_BRANCHES, "");
+
+    assert_eq!(
+        render(&mut git, 12, 80),
+        snapshot(&[
+            "( Local )[ Remote ]( Remotes )( Commits )",
+            "",
+            " Branch: |",
+            "",
+            " Name            Sha      Message",
+            " origin/HEAD     2222222  default branch: origin/main",
+            " origin/feature  1111111  Add feature",
+            " origin/main     2222222  Initial commit",
+            "",
+            "<Ctrl-r> - Refresh, <Ctrl-f> - Fetch all, <Ctrl-o> - Filter remote",
+            "<Ctrl-l> - Open log, <Alt-d> - Update default branch, <Alt-m> - Messages",
+            "/repo",
+        ])
+    );
+}
+
+#[test]
+fn error_message() {
+    let mut git = loaded_git();
+    git.update(key(BareKey::Enter));
+    complete(
+        &mut git,
+        128,
+        "",
+        "fatal: not a git repository\nhint: run git init\n",
+    );
+
+    assert_eq!(
+        render(&mut git, 16, 80),
+        snapshot(&[
+            "[ Local ]( Remote )( Remotes )( Commits )",
+            "",
+            " Branch: |",
+            "",
+            " Name     Upstream                 Sha      Message",
+            " feature  origin/feature: ahead 1  1111111  Add feature",
+            " main     origin/main              2222222  Initial commit",
+            " topic                             3333333  Work in progress",
+            " ──────────────────────────────────────────────────────────────────────────────",
+            " switch main failed with exit code 128",
+            " fatal: not a git repository",
+            " hint: run git init",
+            "",
+            "<Esc> - Dismiss, <PgUp> - Scroll up, <PgDn> - Scroll down",
+            "<Alt-m> - All messages",
+            "/repo",
+        ])
+    );
+}
+
+#[test]
+fn long_message_scrolls() {
+    let mut git = loaded_git();
+    git.update(key(BareKey::Enter));
+    let stderr: Vec<String> = (1..=10).map(|line| format!("line {line}")).collect();
+    complete(&mut git, 1, "", &stderr.join("\n"));
+    render(&mut git, 12, 60);
+
+    git.update(key(BareKey::PageDown));
+    assert_eq!(
+        render(&mut git, 12, 60),
+        snapshot(&[
+            "[ Local ]( Remote )( Remotes )( Commits )",
+            "",
+            " Branch: |",
+            "",
+            " Name     Upstream                 Sha      Message",
+            " main     origin/main              2222222  Initial commit",
+            " ── 4-6/11 ────────────────────────────────────────────────",
+            " line 3",
+            " line 4",
+            " line 5",
+            "",
+            "/repo",
+        ])
+    );
+}
+
+#[test]
+fn message_history() {
+    let mut git = loaded_git();
+    git.update(key(BareKey::Enter));
+    complete(&mut git, 1, "", "first error");
+    git.update(key(BareKey::Esc));
+    git.update(ctrl('d'));
+    complete(&mut git, 1, "", "second error");
+
+    git.update(Event::Key(
+        KeyWithModifier::new(BareKey::Char('m')).with_alt_modifier(),
+    ));
+    assert_eq!(
+        render(&mut git, 20, 60),
+        snapshot(&[
+            "[ Local ]( Remote )( Remotes )( Commits )",
+            "",
+            " Branch: |",
+            "",
+            " Name     Upstream                 Sha      Message",
+            " feature  origin/feature: ahead 1  1111111  Add feature",
+            " main     origin/main              2222222  Initial commit",
+            " topic                             3333333  Work in progres",
+            " ──────────────────────────────────────────────────────────",
+            " Messages",
+            "",
+            " delete main failed with exit code 1",
+            " second error",
+            "",
+            " switch main failed with exit code 1",
+            " first error",
+            "",
+            "<Esc> - Dismiss, <PgUp> - Scroll up, <PgDn> - Scroll down",
+            "<Alt-m> - All messages",
+            "/repo",
+        ])
+    );
+}
+
+#[test]
+fn pending_command_and_status() {
+    let mut git = loaded_git();
+    git.update(key(BareKey::Up));
+    git.update(key(BareKey::Enter));
+    assert_eq!(
+        render(&mut git, 13, 100),
+        snapshot(&[
+            "[ Local ]( Remote )( Remotes )( Commits ) ⠋ switch feature",
+            "",
+            " Branch: |",
+            "",
+            " Name     Upstream                 Sha      Message",
+            " feature  origin/feature: ahead 1  1111111  Add feature",
+            " main     origin/main              2222222  Initial commit",
+            "",
+            "<Ctrl-r> - Refresh, <Ctrl-c> - Create, <Ctrl-d> - Delete, <Ctrl-x> - Force delete",
+            "<Ctrl-z> - Undo delete, <Ctrl-l> - Open log, <Ctrl-p> - Previous branch, <Ctrl-f> - Fetch",
+            "<Ctrl-t> - Set upstream, <Alt-t> - Unset upstream, <Ctrl-e> - Reset to selected",
+            "<Alt-e> - Reset to upstream, <Alt-c> - Cherry-pick, <Alt-n> - Branch from HEAD, <Alt-m> - Messages",
+            "/repo",
+        ])
+    );
+
+    complete(&mut git, 0, "", "Switched to branch 'feature'\n");
+    assert_eq!(
+        render(&mut git, 14, 100),
+        snapshot(&[
+            "[ Local ]( Remote )( Remotes )( Commits )",
+            "",
+            " Branch: |",
+            "",
+            " Name     Upstream                 Sha      Message",
+            " feature  origin/feature: ahead 1  1111111  Add feature",
+            " main     origin/main              2222222  Initial commit",
+            "",
+            "Switched to feature (<Alt-m> for details)",
+            "<Ctrl-r> - Refresh, <Ctrl-c> - Create, <Ctrl-d> - Delete, <Ctrl-x> - Force delete",
+            "<Ctrl-z> - Undo delete, <Ctrl-l> - Open log, <Ctrl-p> - Previous branch, <Ctrl-f> - Fetch",
+            "<Ctrl-t> - Set upstream, <Alt-t> - Unset upstream, <Ctrl-e> - Reset to selected",
+            "<Alt-e> - Reset to upstream, <Alt-c> - Cherry-pick, <Alt-n> - Branch from HEAD, <Alt-m> - Messages",
+            "/repo",
+        ])
+    );
+}
+
+#[test]
+fn ctrl_o_cycles_remote_scope() {
+    let mut git = loaded_git();
+    git.update(key(BareKey::Tab));
+    git.remote_branches_tab.inited = true;
+    git.list_remote_branches(false);
+    complete(
+        &mut git,
+        0,
+        "\
+  origin/HEAD      -> origin/main
+  origin/main      2222222 Initial commit
+  upstream/feature 1111111 Add feature
+  upstream/main    2222222 Initial commit
+",
+        "",
+    );
+
+    let mut scopes = Vec::new();
+    for _ in 0..4 {
+        let screen = render(&mut git, 8, 60);
+        let prompt = screen.lines().nth(2).unwrap().to_string();
+        let names: Vec<String> = git
+            .remote_branches_tab
+            .visible_branches()
+            .map(|(branch, _)| branch.name.clone())
+            .collect();
+        scopes.push((prompt, names.join(" ")));
+        git.update(ctrl('o'));
+    }
+
+    assert_eq!(
+        scopes,
+        [
+            (
+                " Branch: |",
+                "origin/HEAD origin/main upstream/feature upstream/main"
+            ),
+            (" Branch [origin]: |", "origin/HEAD origin/main"),
+            (" Branch [upstream]: |", "upstream/feature upstream/main"),
+            (
+                " Branch: |",
+                "origin/HEAD origin/main upstream/feature upstream/main"
+            ),
+        ]
+        .map(|(prompt, names)| (String::from(prompt), String::from(names)))
+    );
+}
+
+#[test]
+fn hard_reset_confirmation() {
+    let mut git = loaded_git();
+    git.update(key(BareKey::Down));
+    git.update(ctrl('e'));
+    git.update(key(BareKey::Char('h')));
+    complete(&mut git, 0, "1\n", "");
+
+    assert_eq!(
+        render(&mut git, 10, 80),
+        snapshot(&[
+            "[ Local ]( Remote )( Remotes )( Commits )",
+            "",
+            " Reset main to topic (hard)? 1 commit would be lost",
+            "",
+            " Name     Upstream                 Sha      Message",
+            " main     origin/main              2222222  Initial commit",
+            " topic                             3333333  Work in progress",
+            "",
+            "y - Reset, <Esc> - Cancel",
+            "/repo",
+        ])
+    );
+}
+
+#[test]
+fn commit_picker() {
+    let mut git = loaded_git();
+    git.update(key(BareKey::Up));
+    git.update(alt('c'));
+    complete(&mut git, 0, COMMITS, "");
+    git.update(key(BareKey::Tab));
+
+    assert_eq!(
+        render(&mut git, 10, 80),
+        snapshot(&[
+            "[ Local ]( Remote )( Remotes )( Commits )",
+            "",
+            " Cherry-pick from feature: |",
+            "",
+            "    Sha      Author  Message",
+            " +  4444444  Ada     Fix typo",
+            "    5555555  Grace   Add tests",
+            "",
+            "<Tab> - Mark, <Enter> - Cherry-pick, <Esc> - Cancel",
+            "/repo",
+        ])
+    );
+}
+
+#[test]
+fn commits_tab() {
+    let mut git = commits_git(COMMITS);
+
+    assert_eq!(
+        render(&mut git, 11, 80),
+        snapshot(&[
+            "( Local )( Remote )( Remotes )[ Commits ]",
+            "",
+            " Commit: |",
+            "",
+            " Sha      Author  Message",
+            " 4444444  Ada     Fix typo",
+            " 5555555  Grace   Add tests",
+            "",
+            "<Enter> - Checkout, <Ctrl-r> - Refresh, <Ctrl-c> - Create branch, <Ctrl-t> - Tag",
+            "<Alt-c> - Cherry-pick, <Ctrl-l> - Show, <Alt-m> - Messages",
+            "/repo",
+        ])
+    );
+
+    git.update(ctrl('t'));
+    assert_eq!(
+        render(&mut git, 11, 80),
+        snapshot(&[
+            "( Local )( Remote )( Remotes )[ Commits ]",
+            "",
+            " Tag at 4444444: |",
+            "",
+            " Sha      Author  Message",
+            " 4444444  Ada     Fix typo",
+            " 5555555  Grace   Add tests",
+            " 6666666  Ada     Add parser",
+            "",
+            "<Enter> - Create, <Esc> - Cancel",
+            "/repo",
+        ])
+    );
+}
+
+#[test]
+fn detached_head() {
+    let mut git = detached_git();
+    git.update(alt('n'));
+
+    assert_eq!(
+        render(&mut git, 9, 80),
+        snapshot(&[
+            "[ Local ]( Remote )( Remotes )( Commits )",
+            "",
+            " Branch from HEAD: |",
+            "",
+            " Name                        Upstream     Sha      Message",
+            " (HEAD detached at 2222222)               2222222  Initial commit",
+            "",
+            "<Enter> - Create, <Esc> - Cancel",
+            "/repo",
+        ])
+    );
+}
+
+#[test]
+fn tracking_existing_branch() {
+    let mut git = loaded_git();
+    git.update(key(BareKey::Tab));
+    git.remote_branches_tab.inited = true;
+    git.list_remote_branches(false);
+    complete(&mut git, 0, REMOTE_BRANCHES, "");
+    git.update(key(BareKey::Down));
+    git.update(key(BareKey::Enter));
+
+    assert_eq!(
+        render(&mut git, 9, 100),
+        snapshot(&[
+            "( Local )[ Remote ]( Remotes )( Commits )",
+            "",
+            " feature already exists. Switch to it, reset it to origin/feature or use a new name?",
+            "",
+            " Name            Sha      Message",
+            " origin/feature  1111111  Add feature",
+            "",
+            "s - Switch to it, r - Reset it, n - New name, <Esc> - Cancel",
+            "/repo",
+        ])
+    );
+}
+
+#[test]
+fn short_pane_drops_help() {
+    let mut git = loaded_git();
+
+    assert_eq!(
+        render(&mut git, 8, 60),
+        snapshot(&[
+            "[ Local ]( Remote )( Remotes )( Commits )",
+            "",
+            " Branch: |",
+            "",
+            " Name     Upstream                 Sha      Message",
+            " main     origin/main              2222222  Initial commit",
+            "",
+            "/repo",
+        ])
+    );
+}
+
+#[test]
+fn shorter_pane_drops_cwd() {
+    let mut git = loaded_git();
+
+    assert_eq!(
+        render(&mut git, 6, 60),
+        snapshot(&[
+            "[ Local ]( Remote )( Remotes )( Commits )",
+            "",
+            " Branch: |",
+            "",
+            " Name     Upstream                 Sha      Message",
+            " main     origin/main              2222222  Initial commit",
+        ])
+    );
+}
+
+#[test]
+fn failed_background_fetch() {
+    let mut git = loaded_git();
+    git.fetch_all(true);
+    complete(&mut git, 1, "", "fatal: unable to access");
+
+    assert!(!git.messages.is_open());
+    assert_eq!(
+        render(&mut git, 8, 60),
+        snapshot(&[
+            "[ Local ]( Remote )( Remotes )( Commits )",
+            "",
+            " Branch: |",
+            "",
+            " Name     Upstream                 Sha      Message",
+            " main     origin/main              2222222  Initial commit",
+            "",
+            "/repo  (fetch failed, <Alt-m> for details)",
+        ])
+    );
+}
+
+#[test]
+fn pane_too_small() {
+    let mut git = loaded_git();
+
+    assert_eq!(render(&mut git, 5, 60), snapshot(&["Pane too small"]));
+    assert_eq!(render(&mut git, 20, 20), snapshot(&["Pane too small"]));
+}
+
+#[test]
+fn tiny_panes_do_not_panic() {
+    for (rows, cols) in [(0, 0), (1, 1), (2, 80), (4, 4), (6, 12), (40, 3)] {
+        for tab in 0..3 {
+            let mut git = loaded_git();
+            for _ in 0..tab {
+                git.update(key(BareKey::Tab));
+            }
+            render(&mut git, rows, cols);
+            render(&mut git, rows, cols);
+        }
+        render(&mut git(), rows, cols);
+    }
+}